@@ -0,0 +1,47 @@
+fn main() {
+    sum_of_pairs(&[1, 2, 3]);
+}
+
+// An accidental O(n²) that a baseline would catch.
+fn sum_of_pairs(numbers: &[u64]) -> u64 {
+    let mut sum = 0;
+    for a in numbers {
+        for b in numbers {
+            sum += a * b;
+        }
+    }
+    sum
+}
+
+// A baseline keeps the duration of every passing spec in a json file.
+// The first run writes it, later runs compare against it and flag specs
+// that got slower than the regression threshold (20% by default) and
+// by more than the minimum delta (1ms by default), so that specs taking
+// nanoseconds aren't flagged for noise.
+// Set LAB_UPDATE_BASELINE=1 to accept the current durations as the
+// new baseline. Most projects would commit the file next to the tests.
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        let baseline = std::env::temp_dir().join("laboratory-example-baseline.json");
+
+        describe("sum_of_pairs()", |suite| {
+
+            suite.it("should sum the products of every pair", |_| {
+
+                let numbers: Vec<u64> = (1..=500).collect();
+                expect(sum_of_pairs(&numbers)).to_equal(15687562500)
+
+            });
+
+        }).state(NullState).baseline(baseline).regression_threshold(50.0).run()
+
+    }
+}
@@ -0,0 +1,52 @@
+fn main() {
+    fibonacci(20);
+}
+
+fn fibonacci(n: u64) -> u64 {
+    (0..n).fold((0, 1), |(a, b), _| (b, a + b)).0
+}
+
+// Benchmarks live right next to the specs that check the same code.
+// A bench spec is run a number of untimed warm-up rounds and then timed
+// over many iterations. The spec reporter ends with a "Benchmarks"
+// section listing the mean, median, standard deviation and throughput,
+// and the json reporters add them under "bench".
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        describe("fibonacci()", |suite| {
+
+            suite.it("should return 6765 when passed 20", |_| {
+
+                expect(fibonacci(20)).to_equal(6765)
+
+            })
+
+            // 10 warm-up runs and 100 timed runs by default
+            .bench("calculates the 20th number", |_| {
+
+                expect(fibonacci(20)).to_equal(6765)
+
+            })
+
+            .spec(|spec| {
+
+                spec.it("calculates the 90th number", |_| {
+
+                    expect(fibonacci(90)).to_equal(2880067194370816120)
+
+                }).warm_up(100).bench(10_000);
+
+            });
+
+        }).state(NullState).run()
+
+    }
+}
@@ -0,0 +1,57 @@
+use laboratory::{describe, expect, NullState, Suite};
+
+fn add_one(n: u64) -> u64 { n + 1 }
+
+// The harness main (see harness.rs) also takes laboratory's own options,
+// which win over the settings made with the builder methods, so the
+// reporter can be changed without editing the suite:
+//
+//     cargo run --example cli -- --reporter spec --unit us
+//     cargo run --example cli -- --grep "large numbers" --bail
+//     cargo run --example cli -- --reporter json-pretty --output target/lab.json
+//     cargo run --example cli -- --seed 42 --color never
+//
+// grep(), bail() and output() do the same from code.
+
+fn main() {
+    suite().state(NullState).harness();
+}
+
+fn suite() -> Suite<NullState> {
+
+    describe("add_one()", |suite| {
+
+        suite.it("should return 1 when passed 0", |_| {
+
+            expect(add_one(0)).to_equal(1)
+
+        })
+
+        .describe("with large numbers", |suite| {
+
+            suite.it("should return 1,000,001 when passed 1,000,000", |_| {
+
+                expect(add_one(1_000_000)).to_equal(1_000_001)
+
+            });
+
+        });
+
+    })
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::LabResult;
+
+    #[test]
+    fn test() -> LabResult {
+
+        // only the specs under "with large numbers", stopping at the first failure
+        suite().state(NullState).grep("large numbers").bail().run()
+
+    }
+}
@@ -0,0 +1,63 @@
+use laboratory::{describe, expect, NullState, Suite};
+
+fn add_one(n: u64) -> u64 { n + 1 }
+
+// Settings shared by every suite in a crate can live in a laboratory.toml
+// next to Cargo.toml, or under [package.metadata.laboratory] in Cargo.toml:
+//
+//     reporter = "spec"
+//     duration_unit = "us"
+//     color = "auto"
+//     slow = 100                   # milliseconds
+//     timeout = 2000               # milliseconds
+//     retries = 1
+//     output = "target/lab.txt"    # relative to the crate root
+//     snapshot_dir = "tests/snapshots"
+//     baseline = "target/baseline.json"
+//
+// LAB_REPORTER, LAB_DURATION_UNIT, LAB_COLOR, LAB_SLOW, LAB_TIMEOUT,
+// LAB_RETRIES, LAB_OUTPUT, LAB_SNAPSHOT_DIR and LAB_BASELINE win over the
+// file, LAB_CONFIG points at another file, and builder calls win over all
+// of them:
+//
+//     LAB_REPORTER=json-pretty cargo run --example config
+
+fn main() {
+    suite().state(NullState).run().unwrap();
+}
+
+fn suite() -> Suite<NullState> {
+
+    describe("add_one()", |suite| {
+
+        suite.it("should return 1 when passed 0", |_| {
+
+            expect(add_one(0)).to_equal(1)
+
+        })
+
+        .it("should return 1,000,001 when passed 1,000,000", |_| {
+
+            expect(add_one(1_000_000)).to_equal(1_000_001)
+
+        });
+
+    })
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::LabResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test() -> LabResult {
+
+        // the timeout set here wins over the one in laboratory.toml
+        suite().state(NullState).timeout(Duration::from_secs(1)).run()
+
+    }
+}
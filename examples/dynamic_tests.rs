@@ -14,6 +14,7 @@ mod tests {
     #[test]
     fn suite() -> LabResult {
 
+        // See each.rs for a table of cases with templated names.
         describe("add_one()", |suite| {
 
             for i in 0..100 {
@@ -0,0 +1,51 @@
+fn main() {
+    to_roman(4);
+}
+
+fn to_roman(mut n: u32) -> String {
+    let numerals = [(10, "X"), (9, "IX"), (5, "V"), (4, "IV"), (1, "I")];
+    let mut roman = String::new();
+    for (value, numeral) in numerals.iter() {
+        while n >= *value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    roman
+}
+
+// each() turns a table of cases into one spec per case. Every case is
+// reported on its own and runs the suite's hooks like any other spec.
+// The name is a template filled in from the case: "{0}", "{1}"... are the
+// fields of a tuple and "{field}" the fields of a struct that implements
+// serde's Serialize. The json reporters include the case under "case".
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        describe("to_roman()", |suite| {
+
+            let cases = vec![
+                (1, "I"),
+                (4, "IV"),
+                (9, "IX"),
+                (14, "XIV"),
+                (28, "XXVIII")
+            ];
+
+            suite.each(cases, "returns {1} for {0}", |(number, roman), _| {
+
+                expect(to_roman(number)).to_equal(roman.to_string())
+
+            });
+
+        }).state(NullState).run()
+
+    }
+}
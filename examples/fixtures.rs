@@ -0,0 +1,79 @@
+pub struct Connection {
+    pub queries: u32
+}
+impl Connection {
+    pub fn open() -> Connection {
+        Connection { queries: 0 }
+    }
+    pub fn query(&mut self, n: u64) -> u64 {
+        self.queries += 1;
+        n * 2
+    }
+}
+
+pub struct Scratch {
+    pub lines: Vec<String>
+}
+
+fn main() {
+    let mut connection = Connection::open();
+    connection.query(1);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, FixtureScope, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        // Fixtures are typed values that a suite sets up and tears down
+        // for its specs. Each fixture is declared with a scope:
+        //   - FixtureScope::Spec: created fresh for every spec in the suite
+        //     and its child suites
+        //   - FixtureScope::Suite: created once before the suite's before_all
+        //     hook and torn down after its after_all hook
+        //   - FixtureScope::Run: created once for the whole run
+        // Specs then ask for a fixture by its type.
+        describe("Connection", |suite| {
+
+            suite.fixture(FixtureScope::Suite, Connection::open, |connection| {
+
+                println!("closing connection after {} queries", connection.queries);
+
+            })
+
+            .fixture(FixtureScope::Spec, || Scratch { lines: vec![] }, |_| {})
+
+            .it("should double the number", |spec| {
+
+                let connection = spec.fixture::<Connection>()?;
+                let result = connection.borrow_mut().query(2);
+                expect(result).to_equal(4)
+
+            })
+
+            .it("should share the connection between specs", |spec| {
+
+                let connection = spec.fixture::<Connection>()?;
+                connection.borrow_mut().query(3);
+                let queries = connection.borrow().queries;
+                expect(queries).to_equal(2)
+
+            })
+
+            .it("should get a fresh scratch buffer", |spec| {
+
+                let scratch = spec.fixture::<Scratch>()?;
+                scratch.borrow_mut().lines.push("hello".to_string());
+                let lines = scratch.borrow().lines.len();
+                expect(lines).to_equal(1)
+
+            });
+
+        }).state(NullState).run()
+
+    }
+}
@@ -0,0 +1,64 @@
+use laboratory::{describe, expect, NullState, Suite};
+
+fn add_one(n: u64) -> u64 { n + 1 }
+
+// A suite can be the main function of a test target, so that every spec
+// is a test of its own for cargo test and IDE test explorers. Declare the
+// target in Cargo.toml without libtest's harness:
+//
+//     [[test]]
+//     name = "specs"
+//     harness = false
+//
+// and call harness() from its main. libtest's flags work as usual:
+//
+//     cargo test --test specs -- --list
+//     cargo test --test specs -- add_one::should_return_2_when_passed_1 --exact
+//     cargo test --test specs -- --ignored --format json -Z unstable-options
+//
+// This example can be tried with `cargo run --example harness -- --list`.
+
+fn main() {
+    suite().state(NullState).harness();
+}
+
+fn suite() -> Suite<NullState> {
+
+    describe("add_one()", |suite| {
+
+        suite.it("should return 1 when passed 0", |_| {
+
+            expect(add_one(0)).to_equal(1)
+
+        })
+
+        .it("should return 2 when passed 1", |_| {
+
+            expect(add_one(1)).to_equal(2)
+
+        })
+
+        // ignored tests to libtest, run with --ignored or --include-ignored
+        .it_skip("should overflow", |_| {
+
+            expect(add_one(u64::MAX)).to_equal(0)
+
+        });
+
+    })
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::LabResult;
+
+    #[test]
+    fn test() -> LabResult {
+
+        suite().state(NullState).run()
+
+    }
+}
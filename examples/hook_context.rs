@@ -0,0 +1,64 @@
+fn parse_port(input: &str) -> Option<u16> {
+    input.parse().ok()
+}
+
+fn main() {
+    parse_port("8080");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::parse_port;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn test() -> LabResult {
+
+        // Hooks receive a context that gives access to the state just like
+        // before, and for before_each and after_each also describes the spec
+        // being run: its name, full path, tags, attempt number and, after it
+        // ran, its result and duration.
+        describe("parse_port()", |suite| {
+
+            suite.before_each(|ctx| {
+
+                if let Some(spec) = ctx.spec() {
+                    println!("  starting \"{}\" (attempt {})", spec.full_name, spec.attempt);
+                }
+
+            })
+
+            // Only dump diagnostics for specs that failed.
+            .after_each(|ctx| {
+
+                if let Some(spec) = ctx.spec() {
+                    if spec.failed() {
+                        println!("  \"{}\" failed with tags {:?}: {}",
+                            spec.full_name, spec.tags, spec.error().unwrap_or_default());
+                    }
+                }
+
+            })
+
+            .spec(|spec| {
+
+                spec.it("should parse a valid port", |_| {
+
+                    expect(parse_port("8080")).to_equal(Some(8080))
+
+                }).tag("network");
+
+            })
+
+            .it("should reject an out of range port", |_| {
+
+                expect(parse_port("70000")).to_equal(None)
+
+            });
+
+        }).state(NullState).run()
+
+    }
+
+}
@@ -38,6 +38,16 @@ mod tests {
 
             })
 
+            // Hooks of the same kind can be registered more than once;
+            // they run in the order they were registered. before_each hooks
+            // of a parent suite run before those of its child suites, and
+            // the parent's after_each hooks run after the child's.
+            .before_each(|_| {
+
+                println!("  second before_each hook called");
+
+            })
+
             // likewise, we also have actions we want to run
             // after our tests.
             .after_each(|_| {
@@ -93,6 +93,7 @@ mod tests {
         // Describe the crate.
         // And using the describe_import() method we make the two
         // modules child suites to be tested
+        // (see registration.rs to have suites picked up automatically)
         describe("My Crate", |suite| {
             suite
                 .describe_import(add_one::tests::suite())
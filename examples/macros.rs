@@ -0,0 +1,70 @@
+fn main() {
+    add_one(0);
+}
+
+fn add_one(n: u64) -> u64 { n + 1 }
+
+// With the "macros" feature, describe! declares a suite without the
+// closure nesting: specs are written as `it "name" { ... }`, hooks as
+// `before_each { ... }` and child suites as `describe "name" { ... }`.
+// Add `|spec|` or `|ctx|` after a name to use the spec or hook context.
+// Empty and duplicate names are rejected when compiling. The macro
+// returns the Suite so the usual builder calls still apply.
+//
+// #[suite] turns a function taking the suite context into one that
+// returns the Suite, ready to run or to pass to describe_import().
+//
+//     cargo test --example macros --features macros
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, suite, LabResult, NullState, SuiteContext};
+
+    #[suite("add_one() with large numbers")]
+    fn large_numbers(suite: &mut SuiteContext<NullState>) {
+
+        suite.it("should return 1,000,001 when passed 1,000,000", |_| {
+
+            expect(add_one(1_000_000)).to_equal(1_000_001)
+
+        });
+
+    }
+
+    #[test]
+    fn suite() -> LabResult {
+
+        describe! {
+            "add_one()" {
+
+                it "should return 1 when passed 0" {
+                    expect(add_one(0)).to_equal(1)
+                }
+
+                it "should return 2 when passed 1" |spec| {
+                    expect(spec.attempts).to_equal(1)?;
+                    expect(add_one(1)).to_equal(2)
+                }
+
+                describe "when passed the largest number" {
+
+                    it_skip "should overflow" {
+                        expect(add_one(u64::MAX)).to_equal(0)
+                    }
+
+                }
+
+            }
+        }.state(NullState).run()
+
+    }
+
+    #[test]
+    fn imported_suite() -> LabResult {
+
+        large_numbers().state(NullState).run()
+
+    }
+}
@@ -19,9 +19,9 @@ impl Foo {
 }
 
 fn main () {
-    let mut foo = Foo::new();
-    foo.append("fizzbuzz");
-    foo.increase();
+    let mut instance = Foo::new();
+    instance.append("fizzbuzz");
+    instance.increase();
 }
 
 #[cfg(test)]
@@ -0,0 +1,52 @@
+fn main() {
+    reverse(&[1, 2, 3]);
+}
+
+fn reverse(items: &[i32]) -> Vec<i32> {
+    items.iter().rev().copied().collect()
+}
+
+// Property specs check a rule against many generated inputs instead of a
+// few hand-picked ones. Ranges generate numbers, vec_of(), string_of() and
+// any_bool() generate the rest, and tuples of generators pass a tuple of
+// inputs. When an input breaks the rule it is shrunk to the smallest one
+// that still does, and the failure names the seed of the run so the same
+// inputs come back with LAB_SEED=<seed> or the seed() builder.
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, vec_of, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        describe("reverse()", |suite| {
+
+            suite.prop("keeps every item", vec_of(-100..100, 0..20), |items, _| {
+
+                expect(reverse(&items).len()).to_equal(items.len())
+
+            })
+
+            .prop("gives back the input when reversed twice", vec_of(-100..100, 0..20), |items, _| {
+
+                expect(reverse(&reverse(&items))).to_equal(items)
+
+            })
+
+            .prop("puts the last item first", (vec_of(-100..100, 0..20), -100..100), |(mut items, last), _| {
+
+                items.push(last);
+                expect(reverse(&items)[0]).to_equal(last)
+
+            })
+
+            // 100 inputs per property by default
+            .cases(500);
+
+        }).state(NullState).run()
+
+    }
+}
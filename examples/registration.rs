@@ -0,0 +1,83 @@
+fn main() {
+    add_one::add_one(0);
+    multiply_by_two::multiply_by_two(1);
+}
+
+// importing-tests.rs imports every module's suite by hand, which is easy
+// to forget when a module is added. With the "macros" feature a suite
+// function can be marked #[register] instead, and a single root suite
+// picks up every registered suite with describe_registered().
+//
+// #[register] takes a function returning a Suite, or one taking the
+// suite context like #[suite]. Registration happens before main through
+// the inventory crate, on Linux and the other ELF platforms, macOS,
+// Windows and wasm.
+//
+//     cargo test --example registration --features macros
+
+pub mod add_one {
+
+    pub fn add_one (x: u64) -> u64 { x + 1 }
+
+    #[cfg(test)]
+    pub mod tests {
+
+        use super::*;
+        use laboratory::{describe, expect, register, NullState, Suite};
+
+        #[register]
+        pub fn suite() -> Suite<NullState> {
+
+            describe("add_one()", |suite| {
+
+                suite.it("should return 1", |_| {
+                    expect(add_one(0)).to_equal(1)
+                });
+
+            })
+
+        }
+
+    }
+
+}
+
+pub mod multiply_by_two {
+
+    pub fn multiply_by_two (x: u64) -> u64 { x * 2 }
+
+    #[cfg(test)]
+    pub mod tests {
+
+        use super::*;
+        use laboratory::{expect, register, NullState, SuiteContext};
+
+        // the suite is named after the function unless given a name
+        #[register("multiply_by_two()")]
+        fn suite(suite: &mut SuiteContext<NullState>) {
+
+            suite.it("should return 4", |_| {
+                expect(multiply_by_two(2)).to_equal(4)
+            });
+
+        }
+
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use laboratory::{describe, LabResult, NullState};
+
+    #[test]
+    fn test() -> LabResult {
+
+        // no imports needed, registered suites are ordered by module
+        describe("My Crate", |suite| {
+            suite.describe_registered();
+        }).state(NullState).run()
+
+    }
+
+}
@@ -0,0 +1,55 @@
+use std::cell::Cell;
+
+thread_local! {
+    static SEED: Cell<u64> = const { Cell::new(7) };
+}
+
+// A tiny pseudo random coin flip that comes up false now and then.
+fn lucky() -> bool {
+    SEED.with(|seed| {
+        let next = seed.get().wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        seed.set(next);
+        next >> 60 != 0
+    })
+}
+
+fn main() {
+    lucky();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::lucky;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn test() -> LabResult {
+
+        // repeat(n) runs every spec n times and reports the pass rate
+        // along with the min, mean and max durations. Adding
+        // until_failure() stops at the first failing iteration.
+        // Both can also be set on a single spec with suite.spec().
+        describe("lucky", |suite| {
+
+            suite.it("should always be lucky", |_| {
+
+                expect(lucky()).to_be(true)
+
+            })
+
+            .spec(|spec| {
+
+                spec.it("should be lucky once", |_| {
+
+                    expect(lucky() || lucky() || lucky()).to_be(true)
+
+                }).repeat(5);
+
+            });
+
+        }).repeat(50).until_failure().ignore_errors().state(NullState).run()
+
+    }
+
+}
@@ -0,0 +1,59 @@
+use std::cell::Cell;
+
+thread_local! {
+    static CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
+// Fails with a timeout twice before answering.
+fn fetch() -> Result<u64, String> {
+    let call = CALLS.with(|calls| {
+        calls.set(calls.get() + 1);
+        calls.get()
+    });
+    if call < 3 {
+        Err("connection timed out".to_string())
+    } else {
+        Ok(200)
+    }
+}
+
+fn main() {
+    let _ = fetch();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::fetch;
+    use std::time::Duration;
+    use laboratory::{describe, expect, LabResult, NullState, RetryPolicy};
+
+    #[test]
+    fn test() -> LabResult {
+
+        // A retry policy waits between attempts and can limit retries to
+        // the errors worth retrying. Specs that only pass after retrying
+        // are marked as flaky, and the json reporters list every attempt.
+        describe("fetch", |suite| {
+
+            suite.spec(|spec| {
+
+                spec.it("should eventually return 200", |_| {
+
+                    let status = fetch()?;
+                    expect(status).to_equal(200)
+
+                }).retry_policy(
+                    RetryPolicy::new(3)
+                        .exponential(Duration::from_millis(5), 2)
+                        .max_delay(Duration::from_millis(50))
+                        .retry_if(|error| error.contains("timed out"))
+                );
+
+            });
+
+        }).state(NullState).run()
+
+    }
+
+}
@@ -0,0 +1,61 @@
+fn add_one(n: u64) -> u64 { n + 1 }
+
+fn main() {
+    add_one(0);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::add_one;
+    use laboratory::{describe, expect, LabResult, NullState, Suite};
+
+    // An imported suite can bring its own run hooks along.
+    fn imported_suite() -> Suite<NullState> {
+
+        describe("imported", |suite| {
+
+            suite.it("should return 2", |_| {
+
+                expect(add_one(1)).to_equal(2)
+
+            });
+
+        }).after_run(|_| {
+
+            println!("  imported suite cleaned up");
+
+        })
+
+    }
+
+    #[test]
+    fn test() -> LabResult {
+
+        // before_run and after_run hooks run exactly once around the whole
+        // run, including suites brought in with describe_import(). The
+        // after_run hooks are always called, even when a spec fails or panics,
+        // which makes them the place to stop servers or remove temp files.
+        describe("My Crate", |suite| {
+
+            suite.describe_import(imported_suite())
+
+            .it("should return 1", |_| {
+
+                expect(add_one(0)).to_equal(1)
+
+            });
+
+        }).before_run(|_| {
+
+            println!("\n  starting the test server");
+
+        }).after_run(|_| {
+
+            println!("  stopping the test server");
+
+        }).state(NullState).run()
+
+    }
+
+}
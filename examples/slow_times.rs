@@ -21,6 +21,11 @@ fn add_one (x: u64) -> u64 {
 // In the spec reporter, the speed will be he highlighted
 // green, yellow, or red.
 
+// A bare number is read in the suite's duration unit
+// (nanoseconds unless one was chosen), while a
+// std::time::Duration such as Duration::from_secs(3)
+// means the same thing whatever unit is displayed.
+
 #[cfg(test)]
 mod tests {
 
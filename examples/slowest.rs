@@ -0,0 +1,57 @@
+use std::{
+    time::Duration,
+    thread::sleep
+};
+
+fn main() {
+    wait(1);
+}
+
+fn wait(ms: u64) -> u64 {
+    sleep(Duration::from_millis(ms));
+    ms
+}
+
+// Once a suite grows it gets hard to tell where the time goes.
+// slowest(n) ends the run with the n slowest specs and suites,
+// each with its share of the total time, followed by the 50th,
+// 90th, 95th and 99th percentile of the spec durations.
+// The json reporters add the same numbers under "durations".
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        describe("wait()", |suite| {
+
+            suite.it("should wait 1ms", |_| {
+
+                expect(wait(1)).to_equal(1)
+
+            })
+
+            .describe("longer waits", |suite| {
+
+                suite.it("should wait 20ms", |_| {
+
+                    expect(wait(20)).to_equal(20)
+
+                })
+
+                .it("should wait 5ms", |_| {
+
+                    expect(wait(5)).to_equal(5)
+
+                });
+
+            });
+
+        }).state(NullState).milis().slowest(2).run()
+
+    }
+}
@@ -0,0 +1,55 @@
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Add(Box<Expr>, Box<Expr>)
+}
+
+// Parses sums such as "1 + 2 + 3".
+pub fn parse(input: &str) -> Expr {
+    input.split('+')
+        .map(|term| Expr::Number(term.trim().parse().unwrap_or_default()))
+        .reduce(|left, right| Expr::Add(Box::new(left), Box::new(right)))
+        .unwrap_or(Expr::Number(0))
+}
+
+pub fn tokens(input: &str) -> Vec<String> {
+    input.split_whitespace().map(|token| token.to_string()).collect()
+}
+
+fn main() {
+    parse("1 + 2");
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use laboratory::{describe, expect, LabResult, NullState};
+
+    #[test]
+    fn suite() -> LabResult {
+
+        // Instead of writing large expected values by hand, a spec can
+        // compare a value with a snapshot file. The first run writes
+        // snapshots/<suite>/<spec>@<name>.snap and later runs fail with a
+        // diff when the value changes. Run with LAB_UPDATE_SNAPSHOTS=1
+        // to accept the new values. to_match_snapshot() stores the
+        // {:#?} output and to_match_json_snapshot() stores serde json.
+        describe("parse()", |suite| {
+
+            suite.it("should nest additions to the left", |_| {
+
+                expect(parse("1 + 2 + 3")).to_match_snapshot("ast")
+
+            })
+
+            .it("should split tokens on whitespace", |_| {
+
+                expect(tokens("1 + 2")).to_match_json_snapshot("tokens")
+
+            });
+
+        }).state(NullState).run()
+
+    }
+}
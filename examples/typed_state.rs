@@ -0,0 +1,82 @@
+pub struct Connection {
+    pub open: bool
+}
+impl Connection {
+    pub fn open() -> Connection {
+        Connection { open: true }
+    }
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+fn add_one(n: u64) -> u64 { n + 1 }
+
+fn main() {
+    let mut connection = Connection::open();
+    connection.close();
+    add_one(0);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use laboratory::{describe_typed, expect, LabResult};
+
+    #[derive(Clone)]
+    struct Counter(u32);
+
+    #[test]
+    fn suite() -> LabResult {
+
+        // Besides the keyed HashMap, the state can hold one value per type.
+        // Values stored this way must be Clone, so shared resources such as
+        // connections are wrapped in Rc<RefCell<_>>.
+
+        // describe_typed() is describe() with the keyed state type fixed
+        // to NullState, so there is no need to call state(NullState).
+        describe_typed("add_one()", |suite| {
+
+            suite.before_all(|state| {
+
+                state.insert_value(Counter(0));
+                state.insert_value(Rc::new(RefCell::new(Connection::open())));
+
+            }).before_each(|state| {
+
+                if let Some(counter) = state.value_mut::<Counter>() {
+                    counter.0 += 1;
+                }
+
+            }).after_all(|state| {
+
+                if let Some(connection) = state.value::<Rc<RefCell<Connection>>>() {
+                    connection.borrow_mut().close();
+                }
+
+            })
+
+            .it("should return 1", |spec| {
+
+                let count = spec.state.borrow().value::<Counter>().map(|counter| counter.0);
+                expect(count).to_equal(Some(1))?;
+                expect(add_one(0)).to_equal(1)
+
+            })
+
+            .it("should have an open connection", |spec| {
+
+                let open = spec.state.borrow().value::<Rc<RefCell<Connection>>>()
+                    .map(|connection| connection.borrow().open);
+                expect(open).to_equal(Some(true))
+
+            });
+
+        }).run()
+
+    }
+}
@@ -0,0 +1,264 @@
+// Macros that expand to laboratory's describe() and SuiteContext builder
+// calls, enabled on laboratory with the "macros" feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::HashSet;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+  braced,
+  parse_macro_input,
+  Block,
+  Error,
+  FnArg,
+  GenericArgument,
+  Ident,
+  ItemFn,
+  LitStr,
+  Pat,
+  PathArguments,
+  ReturnType,
+  Token,
+  Type
+};
+
+const SPECS: &[&str] = &["it", "it_skip", "it_only", "bench"];
+const HOOKS: &[&str] = &["before_all", "before_each", "after_each", "after_all"];
+const SUITES: &[&str] = &["describe", "describe_skip", "describe_only"];
+
+// "name" { items }
+struct SuiteDef {
+  name: LitStr,
+  items: Vec<Item>
+}
+
+enum Item {
+  // it "name" |spec| { body }, the closure argument being optional
+  Spec { kind: Ident, name: LitStr, arg: Option<Pat>, body: Block },
+  // before_each |ctx| { body }, the closure argument being optional
+  Hook { kind: Ident, arg: Option<Pat>, body: Block },
+  // describe "name" { items }
+  Suite { kind: Ident, suite: SuiteDef }
+}
+
+fn parse_name(input: ParseStream, what: &str) -> syn::Result<LitStr> {
+  let name: LitStr = input.parse()?;
+  if name.value().trim().is_empty() {
+    return Err(Error::new(name.span(), format!("{} name cannot be empty", what)));
+  }
+  Ok(name)
+}
+
+fn parse_arg(input: ParseStream) -> syn::Result<Option<Pat>> {
+  if input.peek(Token![|]) {
+    input.parse::<Token![|]>()?;
+    let arg = Pat::parse_single(input)?;
+    input.parse::<Token![|]>()?;
+    Ok(Some(arg))
+  } else {
+    Ok(None)
+  }
+}
+
+impl Parse for SuiteDef {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let name = parse_name(input, "suite")?;
+    let content;
+    braced!(content in input);
+    let mut items = vec![];
+    let mut spec_names = HashSet::new();
+    let mut suite_names = HashSet::new();
+    while !content.is_empty() {
+      let item: Item = content.parse()?;
+      // Specs and suites are told apart by name in the reports, so two
+      // with the same name in one suite are almost always a copy-paste slip.
+      match &item {
+        Item::Spec { name: spec_name, .. } if !spec_names.insert(spec_name.value()) => {
+          return Err(Error::new(spec_name.span(), format!("duplicate spec name \"{}\" in suite \"{}\"", spec_name.value(), name.value())));
+        },
+        Item::Suite { suite, .. } if !suite_names.insert(suite.name.value()) => {
+          return Err(Error::new(suite.name.span(), format!("duplicate suite name \"{}\" in suite \"{}\"", suite.name.value(), name.value())));
+        },
+        _ => {}
+      }
+      items.push(item);
+    }
+    Ok(SuiteDef { name, items })
+  }
+}
+
+impl Parse for Item {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let kind: Ident = input.parse()?;
+    let keyword = kind.to_string();
+    if SPECS.contains(&keyword.as_str()) {
+      let name = parse_name(input, "spec")?;
+      let arg = parse_arg(input)?;
+      let body = input.parse()?;
+      Ok(Item::Spec { kind, name, arg, body })
+    } else if HOOKS.contains(&keyword.as_str()) {
+      let arg = parse_arg(input)?;
+      let body = input.parse()?;
+      Ok(Item::Hook { kind, arg, body })
+    } else if SUITES.contains(&keyword.as_str()) {
+      let suite = input.parse()?;
+      Ok(Item::Suite { kind, suite })
+    } else {
+      let expected: Vec<&str> = SPECS.iter().chain(HOOKS).chain(SUITES).copied().collect();
+      Err(Error::new(kind.span(), format!("expected one of: {}", expected.join(", "))))
+    }
+  }
+}
+
+// The body of the closure passed to describe() for this suite.
+fn expand_items(suite: &SuiteDef) -> TokenStream2 {
+  let items = suite.items.iter().map(|item| match item {
+    Item::Spec { kind, name, arg, body } => {
+      let arg = arg.as_ref().map_or_else(|| quote!(_), |arg| quote!(#arg));
+      quote! {
+        __lab_suite.#kind(#name, |#arg| -> ::std::result::Result<(), ::std::string::String> #body);
+      }
+    },
+    Item::Hook { kind, arg, body } => {
+      let arg = arg.as_ref().map_or_else(|| quote!(_), |arg| quote!(#arg));
+      quote! {
+        __lab_suite.#kind(|#arg| #body);
+      }
+    },
+    Item::Suite { kind, suite } => {
+      let name = &suite.name;
+      let items = expand_items(suite);
+      quote! {
+        __lab_suite.#kind(#name, |__lab_suite| { #items });
+      }
+    }
+  });
+  quote! { #(#items)* }
+}
+
+// describe! { "suite" { it "spec" { expect(1).to_equal(1) } } } expands to
+// the matching describe() call and returns the Suite, ready for the usual
+// builder calls such as state(), milis() and run().
+#[proc_macro]
+pub fn describe(input: TokenStream) -> TokenStream {
+  let suite = parse_macro_input!(input as SuiteDef);
+  let name = &suite.name;
+  let items = expand_items(&suite);
+  quote!(::laboratory::describe(#name, |__lab_suite| { #items })).into()
+}
+
+// Turns `fn add_one(suite: &mut SuiteContext<NullState>) { ... }` into a
+// function returning the Suite, named after the function unless a name
+// is given as in #[suite("add_one()")].
+#[proc_macro_attribute]
+pub fn suite(attr: TokenStream, item: TokenStream) -> TokenStream {
+  let name = if attr.is_empty() {
+    None
+  } else {
+    Some(parse_macro_input!(attr as LitStr))
+  };
+  let function = parse_macro_input!(item as ItemFn);
+  expand_suite_fn(name, function).unwrap_or_else(Error::into_compile_error).into()
+}
+
+// Like #[suite], and also registers the suite so that
+// SuiteContext::describe_registered() imports it. Works on functions
+// taking the suite context as well as on ones returning a Suite.
+#[proc_macro_attribute]
+pub fn register(attr: TokenStream, item: TokenStream) -> TokenStream {
+  let name = if attr.is_empty() {
+    None
+  } else {
+    Some(parse_macro_input!(attr as LitStr))
+  };
+  let function = parse_macro_input!(item as ItemFn);
+  expand_register(name, function).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_suite_fn(name: Option<LitStr>, function: ItemFn) -> syn::Result<TokenStream2> {
+  let state = context_state(&function)?;
+  let ItemFn { attrs, vis, sig, block } = function;
+  if let ReturnType::Type(_, output) = &sig.output {
+    return Err(Error::new_spanned(output, "a suite function cannot return a value"));
+  }
+  let (pat, ty) = match sig.inputs.first() {
+    Some(FnArg::Typed(arg)) => (&arg.pat, &arg.ty),
+    _ => return Err(Error::new_spanned(&sig.inputs, "expected a single `&mut SuiteContext<T>` argument"))
+  };
+  let name = match name {
+    Some(name) if name.value().trim().is_empty() => {
+      return Err(Error::new(name.span(), "suite name cannot be empty"));
+    },
+    Some(name) => name,
+    None => LitStr::new(&sig.ident.to_string().replace('_', " "), sig.ident.span())
+  };
+  let ident = &sig.ident;
+  Ok(quote! {
+    #(#attrs)*
+    #vis fn #ident() -> ::laboratory::Suite<#state> {
+      ::laboratory::describe(#name, |#pat: #ty| #block)
+    }
+  })
+}
+
+fn expand_register(name: Option<LitStr>, function: ItemFn) -> syn::Result<TokenStream2> {
+  if !function.sig.generics.params.is_empty() {
+    return Err(Error::new_spanned(&function.sig.generics, "a registered suite function cannot be generic"));
+  }
+  let ident = function.sig.ident.clone();
+  let (function, state) = if function.sig.inputs.is_empty() {
+    if let Some(name) = name {
+      return Err(Error::new(name.span(), "name the suite in its describe() call"));
+    }
+    let state = match &function.sig.output {
+      ReturnType::Type(_, output) => type_argument(output, "Suite").cloned(),
+      ReturnType::Default => None
+    }.ok_or_else(|| Error::new_spanned(&function.sig, "expected a function returning `Suite<T>`"))?;
+    (quote!(#function), state)
+  } else {
+    let state = context_state(&function)?;
+    (expand_suite_fn(name, function)?, state)
+  };
+  let name = ident.to_string();
+  Ok(quote! {
+    #function
+    ::laboratory::__private::inventory::submit! {
+      ::laboratory::__private::Registration {
+        module: ::std::module_path!(),
+        name: #name,
+        constructor: &(#ident as fn() -> ::laboratory::Suite<#state>)
+      }
+    }
+  })
+}
+
+// The T of a function's single `&mut SuiteContext<T>` argument.
+fn context_state(function: &ItemFn) -> syn::Result<Type> {
+  let inputs = &function.sig.inputs;
+  match inputs.iter().collect::<Vec<_>>().as_slice() {
+    [FnArg::Typed(arg)] => match &*arg.ty {
+      Type::Reference(reference) if reference.mutability.is_some() => type_argument(&reference.elem, "SuiteContext").cloned(),
+      _ => None
+    },
+    _ => None
+  }.ok_or_else(|| Error::new_spanned(inputs, "expected a single `&mut SuiteContext<T>` argument"))
+}
+
+// The T of `name<T>`.
+fn type_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+  let segment = match ty {
+    Type::Path(path) => path.path.segments.last()?,
+    _ => return None
+  };
+  if segment.ident != name {
+    return None;
+  }
+  match &segment.arguments {
+    PathArguments::AngleBracketed(args) if args.args.len() == 1 => match args.args.first() {
+      Some(GenericArgument::Type(state)) => Some(state),
+      _ => None
+    },
+    _ => None
+  }
+}
@@ -0,0 +1,107 @@
+use laboratory::{describe, describe_typed, expect, register_suite, suite, LabResult, NullState, Suite, SuiteContext};
+
+#[suite]
+fn imported_suite(ctx: &mut SuiteContext<NullState>) {
+  ctx.it("runs", |_spec| {
+    expect(true).to_be(true)
+  });
+}
+
+mod registered {
+
+  use laboratory::{describe, expect, register, NullState, Suite, SuiteContext};
+
+  #[register("registered with a context")]
+  fn with_context(ctx: &mut SuiteContext<NullState>) {
+    ctx.it("runs", |_spec| {
+      expect(true).to_be(true)
+    });
+  }
+
+  #[register]
+  pub fn returning_a_suite() -> Suite<NullState> {
+    describe("registered as a suite", |ctx| {
+      ctx.it("runs", |_spec| {
+        expect(true).to_be(true)
+      });
+    })
+  }
+
+  #[register("registered with a counter")]
+  fn with_a_counter(ctx: &mut SuiteContext<u32>) {
+    ctx.it("runs", |_spec| {
+      expect(true).to_be(true)
+    });
+  }
+
+}
+
+#[test]
+fn expand_a_suite_declared_with_describe() -> LabResult {
+
+  let mut suite: Suite<NullState> = describe! {
+    "my suite" {
+      before_each |ctx| {
+        ctx.spec().map(|spec| spec.attempt).unwrap_or_default();
+      }
+      it "passes" {
+        expect(1).to_equal(1)
+      }
+      it_skip "is skipped" |_spec| {
+        expect(1).to_equal(2)
+      }
+      describe "child" {
+        it "passes too" |spec| {
+          expect(spec.attempts).to_equal(1)
+        }
+      }
+    }
+  };
+
+  suite.run()?;
+
+  expect((suite.context.passed, suite.context.ignored, suite.context.hook_counts.before_each)).to_equal((1, 1, 1))?;
+  expect(suite.context.suites[0].context.passed).to_equal(1)
+
+}
+
+#[test]
+fn name_a_suite_after_its_function() -> LabResult {
+
+  let mut suite = imported_suite();
+  suite.run()?;
+  expect((suite.name.as_str(), suite.context.passed)).to_equal(("imported suite", 1))
+
+}
+
+#[test]
+fn discover_registered_suites() -> LabResult {
+
+  register_suite::<NullState>("registered::at_runtime", "suite", || describe("registered at runtime", |ctx| {
+    ctx.it("runs", |_spec| {
+      expect(true).to_be(true)
+    });
+  }));
+
+  let mut suite = describe_typed("my crate", |ctx| {
+    ctx.describe_registered();
+  });
+  suite.run()?;
+  // Only suites with the same state type are imported.
+  let mut counted: Suite<u32> = describe("counted", |ctx| {
+    ctx.describe_registered();
+  }).state(0);
+  counted.run()?;
+
+  let mut names: Vec<&str> = suite.context.suites.iter().map(|suite| suite.name.as_str()).collect();
+  names.extend(counted.context.suites.iter().map(|suite| suite.name.as_str()));
+  let passed: u32 = suite.context.suites.iter().map(|suite| suite.context.passed).sum::<u32>()
+    + counted.context.suites.iter().map(|suite| suite.context.passed).sum::<u32>();
+  expect((names, passed)).to_equal((vec![
+    "registered as a suite",
+    "registered with a context",
+    "registered at runtime",
+    "registered with a counter"
+  ], 4))
+
+}
@@ -1,6 +1,9 @@
 use std::cmp::PartialEq;
 use std::fmt::Debug;
 use std::{panic::{catch_unwind, UnwindSafe, set_hook, take_hook}};
+use serde::Serialize;
+use serde_json::to_string_pretty;
+use crate::snapshot::assert_snapshot;
 
 pub struct Expect<T>
     where
@@ -13,6 +16,7 @@ impl<T> Expect<T>
         T: PartialEq + Debug,
 {
     pub fn new(expect: T) -> Expect<T> { Expect { result: expect } }
+    #[allow(clippy::self_named_constructors)]
     pub fn expect(result: T) -> Expect<T> {
         Expect { result }
     }
@@ -47,6 +51,19 @@ impl<T> Expect<T>
             Err(format!("Expected {:#?} not to be {:#?}", self.result, control))
         }
     }
+    pub fn to_match_snapshot(&self, name: &str) -> Result<(), String> {
+        assert_snapshot(name, format!("{:#?}\n", self.result))
+    }
+}
+impl<T> Expect<T>
+    where
+        T: PartialEq + Debug + Serialize,
+{
+    pub fn to_match_json_snapshot(&self, name: &str) -> Result<(), String> {
+        let json = to_string_pretty(&self.result)
+            .map_err(|err| format!("Could not serialize snapshot \"{}\": {}", name, err))?;
+        assert_snapshot(name, format!("{}\n", json))
+    }
 }
 
 pub fn expect<T>(result: T) -> Expect<T>
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string_pretty};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// Set to rewrite the baseline file with the durations of the current run.
+pub const UPDATE_BASELINE_VAR: &str = "LAB_UPDATE_BASELINE";
+pub const DEFAULT_REGRESSION_THRESHOLD: f64 = 20.0;
+// Specs that take nanoseconds can double in time from noise alone, so a
+// regression also has to be at least this much slower in absolute terms.
+pub const DEFAULT_REGRESSION_MIN_DELTA: Duration = Duration::from_millis(1);
+
+// The durations of a run, keyed by the spec's path joined with " › ".
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline {
+  pub duration_unit: String,
+  pub total_duration: u128,
+  pub specs: BTreeMap<String, u128>
+}
+impl Baseline {
+  pub fn new(total_duration: Duration) -> Baseline {
+    Baseline {
+      duration_unit: "ns".to_string(),
+      total_duration: total_duration.as_nanos(),
+      specs: BTreeMap::new()
+    }
+  }
+  pub fn load(path: &Path) -> Result<Baseline, String> {
+    let contents = fs::read_to_string(path)
+      .map_err(|err| format!("Could not read baseline {}: {}", path.display(), err))?;
+    from_str(&contents)
+      .map_err(|err| format!("Could not parse baseline {}: {}", path.display(), err))
+  }
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    let contents = to_string_pretty(self)
+      .map_err(|err| format!("Could not serialize baseline: {}", err))?;
+    fs::write(path, contents)
+      .map_err(|err| format!("Could not write baseline {}: {}", path.display(), err))
+  }
+  pub fn get(&self, key: &str) -> Option<Duration> {
+    self.specs.get(key).map(|nanos| Duration::from_nanos((*nanos).min(u64::MAX as u128) as u64))
+  }
+  // A baseline is written when there is none yet or an update was asked for.
+  pub fn should_write(path: &Path) -> bool {
+    !path.exists() || env::var_os(UPDATE_BASELINE_VAR).is_some_and(|value| value != "0")
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineComparison {
+  pub baseline: Duration,
+  // How much slower (positive) or faster (negative) the spec ran, in percent.
+  pub change: f64,
+  pub regressed: bool
+}
+impl BaselineComparison {
+  pub fn new(baseline: Duration, duration: Duration, threshold: f64, min_delta: Duration) -> BaselineComparison {
+    let change = if baseline > Duration::ZERO {
+      (duration.as_secs_f64() - baseline.as_secs_f64()) / baseline.as_secs_f64() * 100.0
+    } else {
+      0.0
+    };
+    BaselineComparison {
+      baseline,
+      change,
+      regressed: change > threshold && duration.saturating_sub(baseline) > min_delta
+    }
+  }
+}
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+use crate::LabResult;
+
+pub const DEFAULT_WARM_UP: u32 = 10;
+pub const DEFAULT_ITERATIONS: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bench {
+  pub warm_up: u32,
+  pub iterations: u32
+}
+impl Default for Bench {
+  fn default() -> Self {
+    Bench {
+      warm_up: DEFAULT_WARM_UP,
+      iterations: DEFAULT_ITERATIONS
+    }
+  }
+}
+impl Bench {
+  pub fn iterations(bench: Option<Bench>, iterations: u32) -> Bench {
+    Bench { iterations, ..bench.unwrap_or_default() }
+  }
+  pub fn warm_up(bench: Option<Bench>, warm_up: u32) -> Bench {
+    Bench { warm_up, ..bench.unwrap_or_default() }
+  }
+  // Runs the warm-up rounds untimed, then times every iteration. The
+  // first error ends the benchmark and is returned with the samples so far.
+  pub fn run<F: FnMut() -> LabResult>(&self, mut f: F) -> (LabResult, BenchStats) {
+    for _ in 0..self.warm_up {
+      if let Err(msg) = f() {
+        return (Err(msg), BenchStats::from_samples(&[]));
+      }
+    }
+    let mut samples = Vec::with_capacity(self.iterations as usize);
+    for _ in 0..self.iterations.max(1) {
+      let start_time = Instant::now();
+      let result = f();
+      samples.push(start_time.elapsed());
+      if result.is_err() {
+        return (result, BenchStats::from_samples(&samples));
+      }
+    }
+    (Ok(()), BenchStats::from_samples(&samples))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchStats {
+  pub iterations: u32,
+  pub mean: Duration,
+  pub median: Duration,
+  pub std_dev: Duration,
+  pub min: Duration,
+  pub max: Duration,
+  // Iterations per second.
+  pub throughput: f64
+}
+impl BenchStats {
+  pub fn from_samples(samples: &[Duration]) -> BenchStats {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let count = sorted.len();
+    let total: Duration = sorted.iter().sum();
+    let mean = if count > 0 { total / count as u32 } else { Duration::ZERO };
+    let median = match count {
+      0 => Duration::ZERO,
+      _ if count % 2 == 0 => (sorted[count / 2 - 1] + sorted[count / 2]) / 2,
+      _ => sorted[count / 2]
+    };
+    let variance = if count > 0 {
+      sorted.iter()
+        .map(|sample| (sample.as_secs_f64() - mean.as_secs_f64()).powi(2))
+        .sum::<f64>() / count as f64
+    } else {
+      0.0
+    };
+    BenchStats {
+      iterations: count as u32,
+      mean,
+      median,
+      std_dev: Duration::from_secs_f64(variance.sqrt()),
+      min: sorted.first().copied().unwrap_or_default(),
+      max: sorted.last().copied().unwrap_or_default(),
+      throughput: if total > Duration::ZERO { count as f64 / total.as_secs_f64() } else { 0.0 }
+    }
+  }
+}
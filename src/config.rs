@@ -0,0 +1,225 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::harness::{parse_color, parse_reporter, parse_unit, HarnessFormat};
+use crate::reporter::{ColorMode, Reporter};
+use crate::suite::{DurationType, SlowThreshold, Suite};
+
+pub const CONFIG_FILE: &str = "laboratory.toml";
+// Points at a config file to use instead of the crate's.
+pub const CONFIG_VAR: &str = "LAB_CONFIG";
+
+// Run defaults from laboratory.toml, or [package.metadata.laboratory] in
+// Cargo.toml when there is no such file, with the LAB_* variables below
+// taking precedence over both. Builder methods and command line options
+// win over all of them.
+//
+//   reporter = "spec"          LAB_REPORTER
+//   duration_unit = "ms"       LAB_DURATION_UNIT
+//   color = "auto"             LAB_COLOR
+//   slow = 100                 LAB_SLOW, in milliseconds
+//   timeout = 2000             LAB_TIMEOUT, in milliseconds
+//   retries = 2                LAB_RETRIES
+//   output = "target/lab.json" LAB_OUTPUT
+//   snapshot_dir = "snapshots" LAB_SNAPSHOT_DIR
+//   baseline = "baseline.json" LAB_BASELINE
+//
+// Relative paths are relative to the file they're in, or to the crate
+// root when they come from a LAB_* variable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+  pub reporter: Option<Reporter>,
+  pub duration_unit: Option<DurationType>,
+  pub color: Option<ColorMode>,
+  pub slow: Option<Duration>,
+  pub timeout: Option<Duration>,
+  pub retries: Option<u32>,
+  pub output: Option<PathBuf>,
+  pub snapshot_dir: Option<PathBuf>,
+  pub baseline: Option<PathBuf>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RawConfig {
+  reporter: Option<String>,
+  duration_unit: Option<String>,
+  color: Option<String>,
+  slow: Option<u64>,
+  timeout: Option<u64>,
+  retries: Option<u32>,
+  output: Option<PathBuf>,
+  snapshot_dir: Option<PathBuf>,
+  baseline: Option<PathBuf>
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+  package: Option<Package>
+}
+#[derive(Deserialize)]
+struct Package {
+  metadata: Option<Metadata>
+}
+#[derive(Deserialize)]
+struct Metadata {
+  laboratory: Option<RawConfig>
+}
+
+static CONFIG: OnceLock<Result<Config, String>> = OnceLock::new();
+
+// The config is read once per process. An invalid one fails every run,
+// so its error is printed here as well, once.
+pub fn get() -> &'static Result<Config, String> {
+  CONFIG.get_or_init(|| {
+    let config = Config::load();
+    if let Err(msg) = &config {
+      eprintln!("error: {}", msg);
+    }
+    config
+  })
+}
+
+fn crate_root() -> PathBuf {
+  env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default()
+}
+
+fn parse_number<N: std::str::FromStr>(name: &str, value: &str) -> Result<N, String> {
+  value.trim().parse().map_err(|_| format!("{} must be a number (was {})", name, value))
+}
+
+impl Config {
+  // The config of the crate being tested, with this process's LAB_*
+  // variables.
+  pub fn load() -> Result<Config, String> {
+    Config::load_with(|name| env::var(name).ok(), &crate_root())
+  }
+  // The config of the crate at `root`, with `var` looking up the LAB_*
+  // variables.
+  pub fn load_with<V: Fn(&str) -> Option<String>>(var: V, root: &Path) -> Result<Config, String> {
+    let env_var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+    let (mut raw, source) = match env_var(CONFIG_VAR) {
+      Some(path) => {
+        let path = root.join(path);
+        (Config::read(&path)?, path)
+      },
+      None if root.join(CONFIG_FILE).exists() => (Config::read(&root.join(CONFIG_FILE))?, root.join(CONFIG_FILE)),
+      None => (Config::read_manifest(&root.join("Cargo.toml"))?, root.join("Cargo.toml"))
+    };
+    let dir = source.parent().unwrap_or(root);
+    for path in [raw.output.as_mut(), raw.snapshot_dir.as_mut(), raw.baseline.as_mut()].iter_mut().flatten() {
+      **path = dir.join(&**path);
+    }
+    let strings = [
+      ("LAB_REPORTER", &mut raw.reporter),
+      ("LAB_DURATION_UNIT", &mut raw.duration_unit),
+      ("LAB_COLOR", &mut raw.color)
+    ];
+    for (name, field) in strings {
+      if let Some(value) = env_var(name) {
+        *field = Some(value);
+      }
+    }
+    let paths = [
+      ("LAB_OUTPUT", &mut raw.output),
+      ("LAB_SNAPSHOT_DIR", &mut raw.snapshot_dir),
+      ("LAB_BASELINE", &mut raw.baseline)
+    ];
+    for (name, field) in paths {
+      if let Some(value) = env_var(name) {
+        *field = Some(PathBuf::from(value));
+      }
+    }
+    if let Some(value) = env_var("LAB_SLOW") {
+      raw.slow = Some(parse_number("LAB_SLOW", &value)?);
+    }
+    if let Some(value) = env_var("LAB_TIMEOUT") {
+      raw.timeout = Some(parse_number("LAB_TIMEOUT", &value)?);
+    }
+    if let Some(value) = env_var("LAB_RETRIES") {
+      raw.retries = Some(parse_number("LAB_RETRIES", &value)?);
+    }
+    Config::validate(raw, root, &source)
+  }
+  // Reads a config file on its own, without the LAB_* overrides.
+  // Relative paths in it are relative to the file's directory.
+  pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Config, String> {
+    let path = path.as_ref();
+    let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    Config::validate(Config::read(path)?, &root, path)
+  }
+  fn validate(raw: RawConfig, root: &Path, source: &Path) -> Result<Config, String> {
+    let invalid = |msg: String| format!("Invalid config {}: {}", source.display(), msg);
+    Ok(Config {
+      reporter: raw.reporter.map(|name| parse_reporter(&name, HarnessFormat::Pretty)).transpose().map_err(invalid)?,
+      duration_unit: raw.duration_unit.map(|name| parse_unit(&name)).transpose().map_err(invalid)?,
+      color: raw.color.map(|name| parse_color(&name)).transpose().map_err(invalid)?,
+      slow: raw.slow.map(Duration::from_millis),
+      timeout: raw.timeout.map(Duration::from_millis),
+      retries: raw.retries,
+      output: raw.output.map(|path| root.join(path)),
+      snapshot_dir: raw.snapshot_dir.map(|path| root.join(path)),
+      baseline: raw.baseline.map(|path| root.join(path))
+    })
+  }
+  fn read(path: &Path) -> Result<RawConfig, String> {
+    let contents = fs::read_to_string(path)
+      .map_err(|err| format!("Could not read config {}: {}", path.display(), err))?;
+    toml::from_str(&contents)
+      .map_err(|err| format!("Could not parse config {}: {}", path.display(), err))
+  }
+  // A missing manifest, or one without [package.metadata.laboratory],
+  // just means there are no defaults.
+  fn read_manifest(path: &Path) -> Result<RawConfig, String> {
+    let contents = match fs::read_to_string(path) {
+      Ok(contents) => contents,
+      Err(_) => return Ok(RawConfig::default())
+    };
+    let manifest: Manifest = toml::from_str(&contents)
+      .map_err(|err| format!("Could not parse [package.metadata.laboratory] in {}: {}", path.display(), err))?;
+    Ok(manifest.package.and_then(|package| package.metadata).and_then(|metadata| metadata.laboratory).unwrap_or_default())
+  }
+  // Settings of the suite itself, applied when it is created so that
+  // builder calls made afterwards win.
+  pub fn apply_to_suite<T>(&self, suite: &mut Suite<T>) {
+    if let Some(reporter) = self.reporter {
+      suite.reporter = reporter;
+    }
+    if let Some(unit) = self.duration_unit {
+      suite.duration_type = unit;
+    }
+    if let Some(color) = self.color {
+      suite.color = color;
+    }
+    if self.timeout.is_some() {
+      suite.timeout = self.timeout;
+    }
+    if self.output.is_some() {
+      suite.output = self.output.clone();
+    }
+    if self.snapshot_dir.is_some() {
+      suite.snapshot_dir = self.snapshot_dir.clone();
+    }
+    if self.baseline.is_some() {
+      suite.baseline = self.baseline.clone();
+    }
+  }
+  // Settings made inside the suite's callback, applied afterwards where
+  // the callback didn't set them. Retries are set on every suite since
+  // they aren't passed down, slow only on the root suite since it is.
+  pub fn apply_to_context<T>(&self, suite: &mut Suite<T>, root: bool) {
+    if suite.context.retries_.is_none() {
+      suite.context.retries_ = self.retries;
+    }
+    if root && suite.context.slow_.is_none() {
+      suite.context.slow_ = self.slow.map(SlowThreshold::Exact);
+    }
+    for child_suite in suite.context.suites.iter_mut() {
+      self.apply_to_context(child_suite, false);
+    }
+  }
+}
@@ -0,0 +1,55 @@
+use serde_json::Value;
+
+// Names a case's spec from a template: "{0}", "{1}"… are the fields of a
+// tuple or array case, "{name}" a field of a struct case and "{}" the
+// whole case. "{{" and "}}" are literal braces and unknown placeholders
+// are left as they are.
+pub fn case_name(template: &str, case: &Value) -> String {
+  let mut name = String::new();
+  let mut rest = template;
+  while let Some(start) = rest.find(['{', '}']) {
+    name.push_str(&rest[..start]);
+    rest = &rest[start..];
+    if rest.starts_with("{{") || rest.starts_with("}}") {
+      name.push_str(&rest[..1]);
+      rest = &rest[2..];
+      continue;
+    }
+    let placeholder = match rest.find('}') {
+      Some(end) if rest.starts_with('{') => &rest[..=end],
+      _ => {
+        name.push_str(&rest[..1]);
+        rest = &rest[1..];
+        continue;
+      }
+    };
+    match field(case, &placeholder[1..placeholder.len() - 1]) {
+      Some(value) => name.push_str(&display(value)),
+      None => name.push_str(placeholder)
+    }
+    rest = &rest[placeholder.len()..];
+  }
+  name.push_str(rest);
+  name
+}
+
+fn field<'a>(case: &'a Value, key: &str) -> Option<&'a Value> {
+  let key = key.trim();
+  if key.is_empty() {
+    return Some(case);
+  }
+  match case {
+    Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+    Value::Object(fields) => fields.get(key),
+    _ if key == "0" => Some(case),
+    _ => None
+  }
+}
+
+// Strings are shown without their quotes, everything else as json.
+fn display(value: &Value) -> String {
+  match value {
+    Value::String(text) => text.to_string(),
+    _ => value.to_string()
+  }
+}
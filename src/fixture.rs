@@ -0,0 +1,93 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixtureScope {
+  Spec,
+  Suite,
+  Run
+}
+
+type Setup = Rc<dyn Fn() -> Rc<dyn Any>>;
+type Teardown = Rc<dyn Fn(&Rc<dyn Any>)>;
+
+#[derive(Clone)]
+pub struct Fixture {
+  pub scope: FixtureScope,
+  pub name: &'static str,
+  type_id: TypeId,
+  setup: Setup,
+  teardown: Teardown
+}
+impl Fixture {
+  pub fn new<F, S, D>(scope: FixtureScope, setup: S, teardown: D) -> Fixture
+  where
+    F: 'static,
+    S: Fn() -> F + 'static,
+    D: Fn(&mut F) + 'static
+  {
+    Fixture {
+      scope,
+      name: std::any::type_name::<F>(),
+      type_id: TypeId::of::<F>(),
+      setup: Rc::new(move || Rc::new(RefCell::new((setup)())) as Rc<dyn Any>),
+      teardown: Rc::new(move |value: &Rc<dyn Any>| {
+        if let Some(cell) = value.downcast_ref::<RefCell<F>>() {
+          (teardown)(&mut cell.borrow_mut());
+        }
+      })
+    }
+  }
+}
+
+pub struct FixtureStore {
+  values: HashMap<TypeId, Rc<dyn Any>>
+}
+impl Default for FixtureStore {
+  fn default() -> Self {
+    FixtureStore::new()
+  }
+}
+impl FixtureStore {
+  pub fn new() -> FixtureStore {
+    FixtureStore { values: HashMap::new() }
+  }
+  pub fn get<F: 'static>(&self) -> Option<Rc<RefCell<F>>> {
+    match self.values.get(&TypeId::of::<F>()) {
+      Some(value) => Rc::downcast::<RefCell<F>>(value.clone()).ok(),
+      None => None
+    }
+  }
+}
+
+// A fixture that was set up, along with whatever value of the same
+// type it shadowed so the outer value can be restored on teardown.
+pub struct ActiveFixture {
+  fixture: Fixture,
+  shadowed: Option<Rc<dyn Any>>
+}
+
+pub fn set_up_fixtures(fixtures: &[Fixture], scope: FixtureScope, store: &Rc<RefCell<FixtureStore>>) -> Vec<ActiveFixture> {
+  let mut active = vec![];
+  for fixture in fixtures.iter().filter(|fixture| fixture.scope == scope) {
+    let value = (fixture.setup)();
+    let shadowed = store.borrow_mut().values.insert(fixture.type_id, value);
+    active.push(ActiveFixture { fixture: fixture.clone(), shadowed });
+  }
+  active
+}
+
+pub fn tear_down_fixtures(active: Vec<ActiveFixture>, store: &Rc<RefCell<FixtureStore>>) {
+  for active_fixture in active.into_iter().rev() {
+    let ActiveFixture { fixture, shadowed } = active_fixture;
+    let value = match shadowed {
+      Some(outer) => store.borrow_mut().values.insert(fixture.type_id, outer),
+      None => store.borrow_mut().values.remove(&fixture.type_id)
+    };
+    if let Some(value) = value {
+      (fixture.teardown)(&value);
+    }
+  }
+}
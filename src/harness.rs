@@ -0,0 +1,169 @@
+use convert_case::{Case, Casing};
+use std::path::PathBuf;
+use crate::reporter::{ColorMode, Reporter};
+use crate::snapshot::slug;
+use crate::suite::DurationType;
+
+// libtest's exit code for failed runs and bad arguments.
+pub const FAILURE_EXIT_CODE: i32 = 101;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HarnessFormat {
+  Pretty,
+  Terse,
+  Json
+}
+
+// The libtest command line flags the harness understands, plus
+// laboratory's own options that override the suite's builder settings:
+//
+//   --reporter <spec|min|dot|list|tap|rust|json|json-pretty|json-tree|
+//               json-tree-pretty|markdown|html|libtest>
+//   --unit <auto|ns|us|ms|s>   --grep <pattern>   --bail
+//   --seed <n>   --output <file>   --color <auto|always|never>
+//
+// Flags that only matter to libtest's own runner, like --test-threads,
+// are accepted and ignored so cargo and IDEs can pass them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarnessArgs {
+  pub list: bool,
+  pub ignored: bool,
+  pub include_ignored: bool,
+  pub exact: bool,
+  pub format: HarnessFormat,
+  pub color: ColorMode,
+  pub filters: Vec<String>,
+  pub skip: Vec<String>,
+  pub reporter: Option<Reporter>,
+  pub unit: Option<DurationType>,
+  pub grep: Option<String>,
+  pub bail: bool,
+  pub seed: Option<u64>,
+  pub output: Option<PathBuf>
+}
+impl Default for HarnessArgs {
+  fn default() -> Self {
+    HarnessArgs {
+      list: false,
+      ignored: false,
+      include_ignored: false,
+      exact: false,
+      format: HarnessFormat::Pretty,
+      color: ColorMode::Auto,
+      filters: vec![],
+      skip: vec![],
+      reporter: None,
+      unit: None,
+      grep: None,
+      bail: false,
+      seed: None,
+      output: None
+    }
+  }
+}
+impl HarnessArgs {
+  pub fn parse<I, S>(args: I) -> Result<HarnessArgs, String>
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>
+  {
+    let mut parsed = HarnessArgs::default();
+    // Resolved after the loop, since libtest depends on --format.
+    let mut reporter = None;
+    let mut args = args.into_iter().map(Into::into);
+    while let Some(arg) = args.next() {
+      let (flag, inline_value) = match arg.split_once('=') {
+        Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+        _ => (arg.to_string(), None)
+      };
+      let mut value = |name: &str| match inline_value.clone().or_else(|| args.next()) {
+        Some(value) => Ok(value),
+        None => Err(format!("Argument to option '{}' missing", name))
+      };
+      match flag.as_str() {
+        "--list" => parsed.list = true,
+        "--ignored" => parsed.ignored = true,
+        "--include-ignored" => parsed.include_ignored = true,
+        "--exact" => parsed.exact = true,
+        "-q" | "--quiet" => parsed.format = HarnessFormat::Terse,
+        "--format" => parsed.format = match value("format")?.as_str() {
+          "pretty" => HarnessFormat::Pretty,
+          "terse" => HarnessFormat::Terse,
+          "json" => HarnessFormat::Json,
+          other => return Err(format!("argument for --format must be pretty, terse, or json (was {})", other))
+        },
+        "--color" => parsed.color = parse_color(&value("color")?).map_err(|msg| format!("argument for --{}", msg))?,
+        "--skip" => parsed.skip.push(value("skip")?),
+        "--reporter" => reporter = Some(value("reporter")?),
+        "--unit" => parsed.unit = Some(parse_unit(&value("unit")?).map_err(|msg| format!("argument for --{}", msg))?),
+        "--grep" => parsed.grep = Some(value("grep")?),
+        "--bail" => parsed.bail = true,
+        "--seed" => parsed.seed = Some(value("seed")?.parse()
+          .map_err(|_| "argument for --seed must be a number".to_string())?),
+        "-o" | "--output" => parsed.output = Some(PathBuf::from(value("output")?)),
+        "--test-threads" | "--logfile" | "-Z" | "--shuffle-seed" => {
+          value(&flag)?;
+        },
+        "--nocapture" | "--show-output" | "--test" | "--bench" | "--report-time" | "--ensure-time"
+          | "--shuffle" | "--exclude-should-panic" | "--force-run-in-process" => {},
+        _ if flag.starts_with('-') => return Err(format!("Unrecognized option: '{}'", flag.trim_start_matches('-'))),
+        _ => parsed.filters.push(arg)
+      }
+    }
+    if let Some(name) = reporter {
+      parsed.reporter = Some(parse_reporter(&name, parsed.format).map_err(|msg| format!("argument for --{}", msg))?);
+    }
+    Ok(parsed)
+  }
+  // Whether a test runs: it matches a filter, if any were given, and
+  // none of the skip patterns.
+  pub fn matches(&self, name: &str) -> bool {
+    let matches = |pattern: &String| if self.exact { name == pattern } else { name.contains(pattern.as_str()) };
+    (self.filters.is_empty() || self.filters.iter().any(matches)) && !self.skip.iter().any(matches)
+  }
+}
+
+// Shared with the config file, which takes the same values.
+pub(crate) fn parse_reporter(name: &str, format: HarnessFormat) -> Result<Reporter, String> {
+  Ok(match name {
+    "spec" => Reporter::Spec,
+    "min" => Reporter::Min,
+    "dot" => Reporter::Dot,
+    "list" => Reporter::List,
+    "tap" => Reporter::Tap,
+    "rust" => Reporter::Rust,
+    "json" => Reporter::Json(false),
+    "json-pretty" => Reporter::Json(true),
+    "json-tree" => Reporter::JsonTree(false),
+    "json-tree-pretty" => Reporter::JsonTree(true),
+    "markdown" => Reporter::Markdown,
+    "html" => Reporter::Html,
+    "libtest" => Reporter::Libtest(format),
+    other => return Err(format!("reporter must be spec, min, dot, list, tap, rust, json, json-pretty, json-tree, json-tree-pretty, markdown, html, or libtest (was {})", other))
+  })
+}
+pub(crate) fn parse_unit(name: &str) -> Result<DurationType, String> {
+  Ok(match name {
+    "auto" => DurationType::Auto,
+    "ns" => DurationType::Nano,
+    "us" | "μs" => DurationType::Micro,
+    "ms" => DurationType::Mil,
+    "s" | "sec" => DurationType::Sec,
+    other => return Err(format!("unit must be auto, ns, us, ms, or s (was {})", other))
+  })
+}
+pub(crate) fn parse_color(name: &str) -> Result<ColorMode, String> {
+  Ok(match name {
+    "auto" => ColorMode::Auto,
+    "always" => ColorMode::Always,
+    "never" => ColorMode::Never,
+    other => return Err(format!("color must be auto, always, or never (was {})", other))
+  })
+}
+
+// The libtest name of a spec: its path in snake case joined with "::",
+// like the rust reporter prints it, minus anything that can't be part of
+// a rust path so names can be used as filters without quoting.
+pub fn test_name(path: &[String]) -> String {
+  path.iter().map(|name| slug(&name.to_case(Case::Snake))).collect::<Vec<String>>().join("::")
+}
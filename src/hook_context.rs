@@ -0,0 +1,56 @@
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+use crate::LabResult;
+use crate::state::State;
+
+#[derive(Debug, Clone)]
+pub struct HookSpec {
+  pub name: String,
+  pub path: Vec<String>,
+  pub full_name: String,
+  pub tags: Vec<String>,
+  pub attempt: u32,
+  pub result: Option<LabResult>,
+  pub duration: Option<Duration>
+}
+impl HookSpec {
+  pub fn passed(&self) -> bool {
+    matches!(self.result, Some(Ok(_)))
+  }
+  pub fn failed(&self) -> bool {
+    matches!(self.result, Some(Err(_)))
+  }
+  pub fn error(&self) -> Option<&str> {
+    match &self.result {
+      Some(Err(msg)) => Some(msg.as_str()),
+      _ => None
+    }
+  }
+}
+
+// Handed to every hook. It dereferences to the state so hooks written
+// against `&mut State<T>` keep working, and for before_each/after_each
+// it also describes the spec being run.
+pub struct HookContext<'a, T> {
+  pub state: &'a mut State<T>,
+  pub spec: Option<HookSpec>
+}
+impl<'a, T> HookContext<'a, T> {
+  pub fn new(state: &'a mut State<T>, spec: Option<HookSpec>) -> HookContext<'a, T> {
+    HookContext { state, spec }
+  }
+  pub fn spec(&self) -> Option<&HookSpec> {
+    self.spec.as_ref()
+  }
+}
+impl<'a, T> Deref for HookContext<'a, T> {
+  type Target = State<T>;
+  fn deref(&self) -> &Self::Target {
+    self.state
+  }
+}
+impl<'a, T> DerefMut for HookContext<'a, T> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    self.state
+  }
+}
@@ -12,14 +12,50 @@ TODO: build docs
 */
 
 mod assertion;
+mod baseline;
+mod bench;
+mod config;
+mod each;
+mod fixture;
+mod harness;
+mod hook_context;
+mod prop;
+mod registry;
+mod repeat;
 mod reporter;
+mod retry;
+mod snapshot;
 mod suite;
 mod suite_context;
 mod spec;
+mod state;
 
 
-pub use suite::{describe, Suite, NullState};
+pub use suite::{describe, describe_typed, DurationType, Suite, NullState, SlowThreshold};
 pub use suite_context::SuiteContext;
 pub use spec::SpecContext;
 pub use assertion::{expect, should_panic, should_not_panic};
+pub use reporter::{ColorMode, Reporter};
+pub use harness::{HarnessArgs, HarnessFormat};
+pub use fixture::FixtureScope;
+pub use state::State;
+pub use hook_context::{HookContext, HookSpec};
+pub use retry::{Attempt, Backoff, RetryPolicy};
+pub use repeat::{Repeat, RepeatStats};
+pub use bench::{Bench, BenchStats};
+pub use baseline::BaselineComparison;
+pub use snapshot::SnapshotSummary;
+pub use config::{Config, CONFIG_FILE, CONFIG_VAR};
+pub use registry::{register_suite, registered_suites};
+pub use prop::{any_bool, string_of, vec_of, AnyBool, Gen, Rng, StringOf, VecOf};
+#[cfg(feature = "macros")]
+pub use laboratory_macros::{describe, register, suite};
+
+// Not public API, used by the code #[register] generates.
+#[doc(hidden)]
+pub mod __private {
+  pub use inventory;
+  pub use crate::registry::Registration;
+}
+
 pub type LabResult = Result<(), String>;
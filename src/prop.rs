@@ -0,0 +1,248 @@
+use std::env;
+use std::fmt::Debug;
+use std::ops::{Range, RangeInclusive};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::LabResult;
+
+// Set to replay a run with the seed printed by a failing property.
+pub const SEED_VAR: &str = "LAB_SEED";
+pub const DEFAULT_CASES: u32 = 100;
+const MAX_SHRINK_STEPS: u32 = 1000;
+
+// A small, seedable splitmix64 generator so runs can be reproduced.
+#[derive(Debug, Clone)]
+pub struct Rng {
+  state: u64
+}
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    Rng { state: seed }
+  }
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+  // A number in 0..bound, or 0 when the bound is 0.
+  pub fn below(&mut self, bound: u128) -> u128 {
+    if bound == 0 {
+      0
+    } else if bound > u64::MAX as u128 {
+      (((self.next_u64() as u128) << 64) | self.next_u64() as u128) % bound
+    } else {
+      ((self.next_u64() as u128) * bound) >> 64
+    }
+  }
+}
+
+// Generates inputs for a property and proposes simpler versions of a
+// failing input, simplest first.
+pub trait Gen {
+  type Value: Clone + Debug;
+  fn generate(&self, rng: &mut Rng) -> Self::Value;
+  fn shrink(&self, _value: &Self::Value) -> Vec<Self::Value> {
+    vec![]
+  }
+}
+
+fn shrink_towards(value: i128, target: i128) -> Vec<i128> {
+  let mut candidates = vec![];
+  if value != target {
+    candidates.push(target);
+    let mut distance = (value - target) / 2;
+    while distance != 0 {
+      candidates.push(value - distance);
+      distance /= 2;
+    }
+    let step = if value > target { value - 1 } else { value + 1 };
+    if !candidates.contains(&step) {
+      candidates.push(step);
+    }
+  }
+  candidates
+}
+
+macro_rules! integer_gen {
+  ($($t:ty),*) => {
+    $(
+      impl Gen for Range<$t> {
+        type Value = $t;
+        fn generate(&self, rng: &mut Rng) -> $t {
+          let (start, end) = (self.start as i128, self.end as i128);
+          if end <= start {
+            return self.start;
+          }
+          (start + rng.below((end - start) as u128) as i128) as $t
+        }
+        fn shrink(&self, value: &$t) -> Vec<$t> {
+          let target = if self.contains(&0) { 0 } else { self.start as i128 };
+          shrink_towards(*value as i128, target).into_iter().map(|candidate| candidate as $t).collect()
+        }
+      }
+      impl Gen for RangeInclusive<$t> {
+        type Value = $t;
+        fn generate(&self, rng: &mut Rng) -> $t {
+          let (start, end) = (*self.start() as i128, *self.end() as i128);
+          if end < start {
+            return *self.start();
+          }
+          (start + rng.below((end - start + 1) as u128) as i128) as $t
+        }
+        fn shrink(&self, value: &$t) -> Vec<$t> {
+          let target = if self.contains(&0) { 0 } else { *self.start() as i128 };
+          shrink_towards(*value as i128, target).into_iter().map(|candidate| candidate as $t).collect()
+        }
+      }
+    )*
+  };
+}
+integer_gen!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+pub struct AnyBool;
+impl Gen for AnyBool {
+  type Value = bool;
+  fn generate(&self, rng: &mut Rng) -> bool {
+    rng.next_u64() & 1 == 1
+  }
+  fn shrink(&self, value: &bool) -> Vec<bool> {
+    if *value { vec![false] } else { vec![] }
+  }
+}
+pub fn any_bool() -> AnyBool {
+  AnyBool
+}
+
+pub struct VecOf<G> {
+  gen: G,
+  len: Range<usize>
+}
+impl<G: Gen> Gen for VecOf<G> {
+  type Value = Vec<G::Value>;
+  fn generate(&self, rng: &mut Rng) -> Self::Value {
+    let len = self.len.generate(rng);
+    (0..len).map(|_| self.gen.generate(rng)).collect()
+  }
+  // Shorter vectors first, then vectors with one simpler element.
+  fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+    let mut candidates = vec![];
+    if value.len() > self.len.start {
+      candidates.push(value[..self.len.start].to_vec());
+      candidates.push(value[..value.len() / 2].to_vec());
+      for i in 0..value.len() {
+        let mut shorter = value.clone();
+        shorter.remove(i);
+        candidates.push(shorter);
+      }
+    }
+    for (i, item) in value.iter().enumerate() {
+      for simpler in self.gen.shrink(item) {
+        let mut candidate = value.clone();
+        candidate[i] = simpler;
+        candidates.push(candidate);
+      }
+    }
+    candidates.retain(|candidate| candidate.len() >= self.len.start);
+    candidates
+  }
+}
+// A vector of generated values with a length in `len`.
+pub fn vec_of<G: Gen>(gen: G, len: Range<usize>) -> VecOf<G> {
+  VecOf { gen, len }
+}
+
+const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+
+pub struct StringOf {
+  chars: VecOf<Range<usize>>
+}
+impl Gen for StringOf {
+  type Value = String;
+  fn generate(&self, rng: &mut Rng) -> String {
+    to_string(&self.chars.generate(rng))
+  }
+  fn shrink(&self, value: &String) -> Vec<String> {
+    let indexes: Vec<usize> = value.bytes()
+      .map(|byte| CHARS.iter().position(|c| *c == byte).unwrap_or_default())
+      .collect();
+    self.chars.shrink(&indexes).iter().map(|indexes| to_string(indexes)).collect()
+  }
+}
+fn to_string(indexes: &[usize]) -> String {
+  indexes.iter().map(|i| CHARS[*i] as char).collect()
+}
+// An alphanumeric string, spaces included, with a length in `len`.
+pub fn string_of(len: Range<usize>) -> StringOf {
+  StringOf { chars: vec_of(0..CHARS.len(), len) }
+}
+
+macro_rules! tuple_gen {
+  ($(($($g:ident $i:tt),*)),*) => {
+    $(
+      impl<$($g: Gen),*> Gen for ($($g,)*) {
+        type Value = ($($g::Value,)*);
+        fn generate(&self, rng: &mut Rng) -> Self::Value {
+          ($(self.$i.generate(rng),)*)
+        }
+        fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+          let mut candidates = vec![];
+          $(
+            for simpler in self.$i.shrink(&value.$i) {
+              let mut candidate = value.clone();
+              candidate.$i = simpler;
+              candidates.push(candidate);
+            }
+          )*
+          candidates
+        }
+      }
+    )*
+  };
+}
+tuple_gen!((A 0), (A 0, B 1), (A 0, B 1, C 2), (A 0, B 1, C 2, D 3));
+
+// The seed for a run: the LAB_SEED variable when set, else the clock.
+pub fn default_seed() -> u64 {
+  match env::var(SEED_VAR).ok().and_then(|seed| seed.trim().parse().ok()) {
+    Some(seed) => seed,
+    None => SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos() as u64).unwrap_or_default()
+  }
+}
+
+// Mixes the run's seed with the spec's name so every property gets its
+// own, still reproducible, sequence of inputs.
+fn spec_seed(seed: u64, name: &str) -> u64 {
+  name.bytes().fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+// Runs the property against `cases` generated inputs and, when one fails,
+// shrinks it to the simplest input that still fails.
+pub fn check<G, F>(gen: &G, seed: u64, name: &str, cases: u32, mut property: F) -> LabResult
+where
+  G: Gen,
+  F: FnMut(G::Value) -> LabResult
+{
+  let mut rng = Rng::new(spec_seed(seed, name));
+  for case in 1..=cases {
+    let input = gen.generate(&mut rng);
+    if let Err(error) = property(input.clone()) {
+      let (mut minimal, mut minimal_error, mut steps) = (input.clone(), error, 0);
+      'shrinking: while steps < MAX_SHRINK_STEPS {
+        for candidate in gen.shrink(&minimal) {
+          if let Err(error) = property(candidate.clone()) {
+            minimal = candidate;
+            minimal_error = error;
+            steps += 1;
+            continue 'shrinking;
+          }
+        }
+        break;
+      }
+      return Err(format!("Property failed on case {} of {} (seed {}, set {}={} to reproduce)\nminimal input: {:?}\noriginal input: {:?} ({} shrink step{})\n{}",
+        case, cases, seed, SEED_VAR, seed, minimal, input, steps, if steps == 1 { "" } else { "s" }, minimal_error));
+    }
+  }
+  Ok(())
+}
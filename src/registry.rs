@@ -0,0 +1,48 @@
+use std::any::Any;
+use std::sync::Mutex;
+use crate::suite::Suite;
+
+// A registered suite. #[register] submits these to inventory, which
+// collects them before main on every platform it supports, wasm included.
+#[doc(hidden)]
+pub struct Registration {
+  pub module: &'static str,
+  pub name: &'static str,
+  // A Constructor<T>, kept as Any so suites of every state type can
+  // share one registry.
+  pub constructor: &'static (dyn Any + Send + Sync)
+}
+
+inventory::collect!(Registration);
+
+type Constructor<T> = fn() -> Suite<T>;
+
+// Suites registered at runtime with register_suite().
+static REGISTRY: Mutex<Vec<Registration>> = Mutex::new(Vec::new());
+
+// Adds a suite constructor to the registry at runtime, for suites that
+// aren't declared with #[register].
+pub fn register_suite<T: 'static>(module: &'static str, name: &'static str, constructor: Constructor<T>) {
+  let mut registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+  registry.push(Registration {
+    module,
+    name,
+    constructor: Box::leak(Box::new(constructor))
+  });
+}
+
+// Builds every registered suite with state T, ordered by module path and
+// function name since constructors run before main in no particular order.
+pub fn registered_suites<T: 'static>() -> Vec<Suite<T>> {
+  let constructor = |registration: &Registration| {
+    registration.constructor.downcast_ref::<Constructor<T>>()
+      .map(|constructor| (registration.module, registration.name, *constructor))
+  };
+  let mut constructors: Vec<(&'static str, &'static str, Constructor<T>)> = {
+    let registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+    registry.iter().filter_map(constructor).collect()
+  };
+  constructors.extend(inventory::iter::<Registration>.into_iter().filter_map(constructor));
+  constructors.sort_by_key(|(module, name, _)| (*module, *name));
+  constructors.into_iter().map(|(_, _, constructor)| constructor()).collect()
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+use crate::retry::Attempt;
+
+// Used by until_failure() when no repeat count was given.
+pub const UNTIL_FAILURE_LIMIT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Repeat {
+  pub times: u32,
+  pub until_failure: bool
+}
+impl Repeat {
+  pub fn times(repeat: Option<Repeat>, times: u32) -> Repeat {
+    Repeat {
+      times,
+      until_failure: repeat.is_some_and(|repeat| repeat.until_failure)
+    }
+  }
+  pub fn until_failure(repeat: Option<Repeat>) -> Repeat {
+    Repeat {
+      times: repeat.map_or(UNTIL_FAILURE_LIMIT, |repeat| repeat.times),
+      until_failure: true
+    }
+  }
+}
+
+// What repeatedly running a spec turned up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatStats {
+  pub iterations: u32,
+  pub passed: u32,
+  pub failed: u32,
+  pub min_duration: Duration,
+  pub mean_duration: Duration,
+  pub max_duration: Duration,
+  pub first_failure: Option<u32>
+}
+impl RepeatStats {
+  pub fn from_attempts(attempts: &[Attempt]) -> RepeatStats {
+    let iterations = attempts.len() as u32;
+    let failed = attempts.iter().filter(|attempt| attempt.error.is_some()).count() as u32;
+    let total: Duration = attempts.iter().map(|attempt| attempt.duration).sum();
+    RepeatStats {
+      iterations,
+      passed: iterations - failed,
+      failed,
+      min_duration: attempts.iter().map(|attempt| attempt.duration).min().unwrap_or_default(),
+      mean_duration: if iterations > 0 { total / iterations } else { Duration::ZERO },
+      max_duration: attempts.iter().map(|attempt| attempt.duration).max().unwrap_or_default(),
+      first_failure: attempts.iter().position(|attempt| attempt.error.is_some()).map(|i| i as u32 + 1)
+    }
+  }
+  pub fn pass_rate(&self) -> f64 {
+    if self.iterations == 0 {
+      0.0
+    } else {
+      self.passed as f64 / self.iterations as f64
+    }
+  }
+}
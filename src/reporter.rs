@@ -1,15 +1,22 @@
 use convert_case::{Case, Casing};
-use console::{style};
+use console::{style, Term};
+use crate::harness::{test_name, HarnessFormat};
+use crate::spec::Spec;
 use crate::suite::{
-  Duration,
   DurationType,
   Speed,
   Suite
 };
 use serde::{Serialize};
-use serde_json::{to_string, to_string_pretty};
-use std::fmt::Display;
+use serde_json::{json, to_string, to_string_pretty, Value};
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Reporter{
   Spec,
   Min,
@@ -17,19 +24,61 @@ pub enum Reporter{
   List,
   Rust,
   Tap,
-  Json(bool) // true = pretty
+  Json(bool), // true = pretty
+  JsonTree(bool), // true = pretty
+  Markdown,
+  Html,
+  Libtest(HarnessFormat)
 }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+  Auto,
+  Always,
+  Never
+}
+
+pub enum Banner {
+  Default,
+  Custom(String, String),
+  Off
+}
+
+thread_local! {
+  static COLOR_ENABLED: Cell<bool> = const { Cell::new(true) };
+  static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// Reports are printed through out! and outln! so they can be captured
+// and written to a file instead of stdout.
+macro_rules! out {
+  ($($arg:tt)*) => { write_out(format_args!($($arg)*)) };
+}
+macro_rules! outln {
+  () => { out!("\n") };
+  ($($arg:tt)*) => { out!("{}\n", format_args!($($arg)*)) };
+}
+
+fn write_out(args: fmt::Arguments) {
+  let captured = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+    Some(buffer) => fmt::Write::write_fmt(buffer, args).is_ok(),
+    None => false
+  });
+  if !captured {
+    print!("{}", args);
+  }
+}
+
 enum SpeedDisplay {
-  Fast(Duration),
-  OnTime(Duration),
-  Slow(Duration)
+  Fast(String),
+  OnTime(String),
+  Slow(String)
 }
-impl SpeedDisplay {
-  pub fn to_string(&self) -> String {
+impl fmt::Display for SpeedDisplay {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
-      SpeedDisplay::Fast(duration) => green(duration.to_string()),
-      SpeedDisplay::OnTime(duration) => yellow(duration.to_string()),
-      SpeedDisplay::Slow(duration) => red(duration.to_string())
+      SpeedDisplay::Fast(duration) => write!(f, "{}", green(duration.as_str())),
+      SpeedDisplay::OnTime(duration) => write!(f, "{}", yellow(duration.as_str())),
+      SpeedDisplay::Slow(duration) => write!(f, "{}", red(duration.as_str()))
     }
   }
 }
@@ -38,9 +87,59 @@ impl SpeedDisplay {
 struct JsonSpecReport {
   pub title: String,
   pub full_title: String,
-  pub duration: u128,
+  pub path: Vec<String>,
+  pub status: &'static str,
+  pub duration: f64,
+  pub duration_unit: &'static str,
   pub error: Option<String>,
-  pub attempts: u32
+  pub attempts: u32,
+  pub flaky: bool,
+  pub attempt_history: Vec<JsonAttemptReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repeat: Option<JsonRepeatReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub bench: Option<JsonBenchReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub baseline: Option<JsonBaselineReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub case: Option<Value>
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct JsonBaselineReport {
+  pub duration: f64,
+  pub change: f64,
+  pub regressed: bool
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct JsonBenchReport {
+  pub iterations: u32,
+  pub mean: f64,
+  pub median: f64,
+  pub std_dev: f64,
+  pub min: f64,
+  pub max: f64,
+  pub throughput: f64
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct JsonRepeatReport {
+  pub iterations: u32,
+  pub passed: u32,
+  pub failed: u32,
+  pub pass_rate: f64,
+  pub min_duration: f64,
+  pub mean_duration: f64,
+  pub max_duration: f64,
+  pub first_failure: Option<u32>
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct JsonAttemptReport {
+  pub attempt: u32,
+  pub error: Option<String>,
+  pub duration: f64
 }
 
 impl JsonSpecReport {
@@ -48,12 +147,18 @@ impl JsonSpecReport {
     JsonSpecReport {
       title: self.title.to_string(),
       full_title: self.full_title.to_string(),
+      path: self.path.clone(),
+      status: self.status,
       duration: self.duration,
-      error: match &self.error {
-        Some(msg) => Some(msg.to_string()),
-        None => None
-      },
-      attempts: self.attempts
+      duration_unit: self.duration_unit,
+      error: self.error.as_ref().map(|msg| msg.to_string()),
+      attempts: self.attempts,
+      flaky: self.flaky,
+      attempt_history: self.attempt_history.clone(),
+      repeat: self.repeat.clone(),
+      bench: self.bench.clone(),
+      baseline: self.baseline.clone(),
+      case: self.case.clone()
     }
   }
 }
@@ -67,7 +172,8 @@ struct JsonStats {
   pub failing: u32,
   pub start: String,
   pub end: String,
-  pub duration: u128
+  pub duration: f64,
+  pub duration_unit: &'static str
 }
 
 #[derive(Debug, Serialize)]
@@ -76,7 +182,96 @@ struct JsonReport {
   pub tests: Vec<JsonSpecReport>,
   pub passing: Vec<JsonSpecReport>,
   pub pending: Vec<JsonSpecReport>,
-  pub failing: Vec<JsonSpecReport>
+  pub failing: Vec<JsonSpecReport>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub regressions: Vec<JsonSpecReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub durations: Option<JsonDurationReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub snapshots: Option<JsonSnapshotReport>
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSuiteStats {
+  pub tests: u32,
+  pub passing: u32,
+  pub pending: u32,
+  pub failing: u32,
+  pub duration: f64,
+  pub total_duration: f64,
+  pub duration_unit: &'static str
+}
+
+#[derive(Debug, Serialize)]
+struct JsonHookReport {
+  pub before_all: u32,
+  pub before_each: u32,
+  pub after_each: u32,
+  pub after_all: u32
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSuiteReport {
+  pub title: String,
+  pub path: Vec<String>,
+  pub skipped: bool,
+  pub start: Option<String>,
+  pub end: Option<String>,
+  pub stats: JsonSuiteStats,
+  pub hooks: JsonHookReport,
+  pub specs: Vec<JsonSpecReport>,
+  pub suites: Vec<JsonSuiteReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub durations: Option<JsonDurationReport>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub snapshots: Option<JsonSnapshotReport>
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSnapshotReport {
+  pub matched: u32,
+  pub failed: u32,
+  pub written: Vec<String>,
+  pub updated: Vec<String>,
+  pub obsolete: Vec<String>
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDurationReport {
+  pub slowest_specs: Vec<JsonSlowReport>,
+  pub slowest_suites: Vec<JsonSlowReport>,
+  pub percentiles: JsonPercentiles,
+  pub duration_unit: &'static str
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSlowReport {
+  pub title: String,
+  pub path: Vec<String>,
+  pub duration: f64,
+  pub share: f64
+}
+
+#[derive(Debug, Serialize)]
+struct JsonPercentiles {
+  pub p50: f64,
+  pub p90: f64,
+  pub p95: f64,
+  pub p99: f64
+}
+
+// The slowest specs and suites of a run and the spread of spec durations,
+// shown at the end of the run when Suite::slowest() is set.
+struct DurationSummary {
+  pub slowest_specs: Vec<SlowEntry>,
+  pub slowest_suites: Vec<SlowEntry>,
+  pub percentiles: [(u32, Duration); 4]
+}
+
+struct SlowEntry {
+  pub path: Vec<String>,
+  pub duration: Duration,
+  pub share: f64
 }
 
 struct MinReporterStats {
@@ -94,64 +289,129 @@ struct DotReporterStats {
 }
 
 fn red<T: Into<String> + Display>(text: T) -> String {
-  style(text).red().for_stdout().to_string()
+  style(text).red().force_styling(color_enabled()).to_string()
 }
 fn green<T: Into<String> + Display>(text: T) -> String {
-  style(text).green().for_stdout().to_string()
+  style(text).green().force_styling(color_enabled()).to_string()
 }
 fn cyan<T: Into<String> + Display>(text: T) -> String {
-  style(text).cyan().for_stdout().to_string()
+  style(text).cyan().force_styling(color_enabled()).to_string()
 }
 fn dim<T: Into<String> + Display>(text: T) -> String {
-  style(text).dim().for_stdout().to_string()
+  style(text).dim().force_styling(color_enabled()).to_string()
 }
 fn yellow<T: Into<String> + Display>(text: T) -> String {
-  style(text).yellow().for_stdout().to_string()
+  style(text).yellow().force_styling(color_enabled()).to_string()
+}
+
+fn color_enabled() -> bool {
+  COLOR_ENABLED.with(|enabled| enabled.get())
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+  match env::var(name) {
+    Ok(value) => Some(!value.is_empty() && value != "0"),
+    Err(_) => None
+  }
+}
+
+fn is_machine_format(reporter: &Reporter) -> bool {
+  matches!(reporter,
+    Reporter::Tap | Reporter::Json(_) | Reporter::JsonTree(_) | Reporter::Markdown | Reporter::Html
+      | Reporter::Libtest(HarnessFormat::Json))
+}
+
+// NO_COLOR wins over CLICOLOR_FORCE, which wins over terminal detection.
+// Machine and document formats are never styled unless colour is explicitly forced.
+fn resolve_color(mode: ColorMode, reporter: &Reporter) -> bool {
+  match mode {
+    ColorMode::Always => true,
+    ColorMode::Never => false,
+    ColorMode::Auto => {
+      if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        false
+      } else if env_flag("CLICOLOR_FORCE") == Some(true) {
+        true
+      } else if is_machine_format(reporter) || env_flag("CLICOLOR") == Some(false) {
+        false
+      } else {
+        Term::stdout().features().colors_supported()
+      }
+    }
+  }
+}
+
+// The default banner is only shown for console reporters
+// so it never ends up inside machine readable output or documents.
+fn banner_text<T>(suite: &Suite<T>) -> Option<(String, String)> {
+  match &suite.banner {
+    Banner::Default => if is_machine_format(&suite.reporter) {
+      None
+    } else {
+      Some(("### Lab Results Start ###".to_string(), "### Lab Results End ###".to_string()))
+    },
+    Banner::Custom(start, end) => Some((start.to_string(), end.to_string())),
+    Banner::Off => None
+  }
 }
 
-fn header() {
-  print!("\n\n### Lab Results Start ###\n\n");
+fn header(banner: &Option<(String, String)>) {
+  if let Some((start, _)) = banner {
+    out!("\n\n{}\n\n", start);
+  }
 }
 
-fn footer() {
-  println!("\n### Lab Results End ###\n\n");
+fn footer(banner: &Option<(String, String)>) {
+  if let Some((_, end)) = banner {
+    outln!("\n{}\n\n", end);
+  }
 }
 
 fn get_lines_for_spec<T>(suite: &Suite<T>, depth: u32, stats: &mut MinReporterStats) {
 
-  println!("{}{}", suite_spacing(depth), suite.name.to_string());
+  outln!("{}{}", suite_spacing(depth), suite.name);
   for spec in &suite.context.specs {
     if let Some(result) = &spec.result {
       if let Err(msg) = result {
-        println!("{}{} {}", 
+        outln!("{}{} {}", 
           line_spacing(depth),
-          red(&format!("{})", stats.failed)),
-          red(&spec.name.to_string()));
-        stats.error_lines.push(red(&format!("{}) {}: {}", stats.failed, spec.name, msg)));
+          red(format!("{})", stats.failed)),
+          red(spec.name.to_string()));
+        stats.error_lines.push(red(format!("{}) {}: {}", stats.failed, spec.name, msg)));
         stats.failed += 1;
       } else {
-        let duration = match suite.duration_type {
-          DurationType::Nano => Duration::Nano(spec.duration),
-          DurationType::Micro => Duration::Micro(spec.duration),
-          DurationType::Mil => Duration::Mil(spec.duration),
-          DurationType::Sec => Duration::Sec(spec.duration)
-        };
+        let duration = format_duration(suite.duration_type, spec.duration);
         let speed_display = match spec.context.speed_result {
           Speed::Fast => SpeedDisplay::Fast(duration),
           Speed::OnTime => SpeedDisplay::OnTime(duration),
           Speed::Slow => SpeedDisplay::Slow(duration)
         };
-        println!("{}{}  {} {}", 
+        let flaky = match flaky_note(spec) {
+          Some(note) => format!(" {}", yellow(format!("({})", note))),
+          None => String::new()
+        };
+        let repeat = match repeat_note(spec, suite.duration_type) {
+          Some(note) => format!(" {}", dim(format!("[{}]", note))),
+          None => String::new()
+        };
+        let regression = match regression_note(spec, suite.duration_type) {
+          Some(note) => format!(" {}", red(format!("(regressed {})", note))),
+          None => String::new()
+        };
+        outln!("{}{}  {} {}{}{}{}", 
           line_spacing(depth),
           green("✓"),
-          dim(&spec.name.to_string()), 
-          speed_display.to_string());
+          dim(spec.name.to_string()), 
+          speed_display,
+          flaky,
+          repeat,
+          regression);
           stats.passed += 1;
       }
     } else {
-      println!("{}   {}", 
+      outln!("{}   {}", 
         line_spacing(depth), 
-        dim(&spec.name.to_string()));
+        dim(spec.name.to_string()));
         stats.pending += 1;
     }
   }
@@ -203,7 +463,7 @@ fn get_lines_for_min<T>(suite: &Suite<T>, stats: &mut MinReporterStats, prefix:
       if let Err(msg) = result {
         stats.failed += 1;
         stats.error_lines.push(
-          red(&format!("{}) {}\n   {}{}\n{}  Error: {}", 
+          red(format!("{}) {}\n   {}{}\n{}  Error: {}", 
             stats.failed, prefix, 
             line_spacing_for_min(depth), 
             spec.name, 
@@ -260,25 +520,35 @@ fn get_list<T>(suite: &Suite<T>, stats: &mut MinReporterStats, prefix: String) {
   for spec in &suite.context.specs {
     if let Some(result) = &spec.result {
       if let Err(msg) = result {
-        println!("✖ {}",
+        outln!("✖ {}",
           red(format!("{} {}: {}", prefix, spec.name, msg))
         );
         stats.failed += 1;
       } else {
-        let duration = match suite.duration_type {
-          DurationType::Nano => Duration::Nano(spec.duration),
-          DurationType::Micro => Duration::Micro(spec.duration),
-          DurationType::Mil => Duration::Mil(spec.duration),
-          DurationType::Sec => Duration::Sec(spec.duration)
+        let duration = format_duration(suite.duration_type, spec.duration);
+        let flaky = match flaky_note(spec) {
+          Some(note) => format!(" {}", yellow(format!("({})", note))),
+          None => String::new()
+        };
+        let repeat = match repeat_note(spec, suite.duration_type) {
+          Some(note) => format!(" {}", dim(format!("[{}]", note))),
+          None => String::new()
         };
-        println!("✓ {}{}",
+        let regression = match regression_note(spec, suite.duration_type) {
+          Some(note) => format!(" {}", red(format!("(regressed {})", note))),
+          None => String::new()
+        };
+        outln!("✓ {}{}{}{}{}",
           green(format!("{} {}", prefix, spec.name)),
-          dim(format!(": {}", duration.to_string()))
+          dim(format!(": {}", duration)),
+          flaky,
+          repeat,
+          regression
         );
         stats.passed += 1;
       }
     } else {
-      println!("  {}",
+      outln!("  {}",
         dim(format!("{} {}", prefix, spec.name))
       );
       stats.pending += 1;
@@ -294,23 +564,26 @@ fn get_tap_list<T>(suite: &Suite<T>, lines: &mut Vec<String>, count: &mut u32, p
     *count += 1;
     if let Some(result) = &spec.result {
       if let Err(_msg) = result {
-        lines.push(format!("{} {} - {}",
+        lines.push(format!("{} {} - {} {}",
           red("not ok"),
           count,
-          format!("{} {}", prefix, spec.name)
+          prefix,
+          spec.name
         ));
       } else {
-        lines.push(format!("{} {} - {}",
+        lines.push(format!("{} {} - {} {}",
           green("ok"),
           count,
-          format!("{} {}", prefix, spec.name)
+          prefix,
+          spec.name
         ));
       }
     } else {
-      lines.push(format!("{} {} - {}",
+      lines.push(format!("{} {} - # skip {} {}",
         green("ok"),
         count,
-        format!("# skip {} {}", prefix, spec.name)
+        prefix,
+        spec.name
       ));
     }
   }
@@ -324,22 +597,22 @@ fn get_count<T>(suite: &Suite<T>) -> u32 {
   for child_suite in &suite.context.suites {
     count += get_count(child_suite);
   }
-  return count;
+  count
 }
 
 fn get_list_for_rust<T>(suite: &Suite<T>, stats: &mut MinReporterStats, prefix: String) {
   for spec in &suite.context.specs {
     if let Some(result) = &spec.result {
       if let Err(_msg) = result {
-        println!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), red("FAILED"));
+        outln!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), red("FAILED"));
         stats.error_lines.push(format!("{}::{}", prefix, spec.name.to_case(Case::Snake)));
         stats.failed += 1;
       } else {
-        println!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), green("ok"));
+        outln!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), green("ok"));
         stats.passed += 1;
       }
     } else {
-      println!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), cyan("ignored"));
+      outln!("test {}::{} ... {}", prefix, spec.name.to_case(Case::Snake), cyan("ignored"));
       stats.pending += 1;
     }
   }
@@ -348,14 +621,154 @@ fn get_list_for_rust<T>(suite: &Suite<T>, stats: &mut MinReporterStats, prefix:
   }
 }
 
-fn get_stats_for_json<T>(suite: &Suite<T>, stats: &mut JsonReport, prefix: String) {
+fn get_libtest_tests<'a, T>(suite: &'a Suite<T>, tests: &mut Vec<(String, &'a Spec<T>)>) {
   for spec in &suite.context.specs {
+    let mut spec_path = suite.path.clone();
+    spec_path.push(spec.name.to_string());
+    tests.push((test_name(&spec_path), spec));
+  }
+  for child_suite in &suite.context.suites {
+    get_libtest_tests(child_suite, tests);
+  }
+}
+
+// The output of `cargo test -- --list`: "name: test" lines, or discovery
+// events for --format json.
+pub fn report_test_list<T>(suite: &Suite<T>) {
+  let format = match suite.reporter {
+    Reporter::Libtest(format) => format,
+    _ => HarnessFormat::Pretty
+  };
+  let mut tests = vec![];
+  get_libtest_tests(suite, &mut tests);
+  let ignored = tests.iter().filter(|(_, spec)| spec.skip).count();
+  if format == HarnessFormat::Json {
+    outln!("{}", json!({ "type": "suite", "event": "discovery" }));
+  }
+  for (name, spec) in &tests {
+    match format {
+      HarnessFormat::Json => outln!("{}", json!({ "type": "test", "event": "discovered", "name": name, "ignore": spec.skip })),
+      _ => outln!("{}: test", name)
+    }
+  }
+  match format {
+    HarnessFormat::Pretty => outln!("\n{} test{}, 0 benchmarks", tests.len(), if tests.len() == 1 { "" } else { "s" }),
+    HarnessFormat::Terse => {},
+    HarnessFormat::Json => outln!("{}", json!({
+      "type": "suite",
+      "event": "completed",
+      "tests": tests.len(),
+      "benchmarks": 0,
+      "total": tests.len(),
+      "ignored": ignored
+    }))
+  }
+}
+
+// Results in libtest's pretty, terse or json output so cargo and IDE test
+// explorers can read them.
+fn print_libtest<T>(suite: &Suite<T>, format: HarnessFormat) {
+  let mut tests = vec![];
+  get_libtest_tests(suite, &mut tests);
+  let (mut passed, mut failed, mut ignored) = (0, 0, 0);
+  let mut failures = vec![];
+  match format {
+    HarnessFormat::Json => outln!("{}", json!({ "type": "suite", "event": "started", "test_count": tests.len() })),
+    _ => outln!("\nrunning {} test{}", tests.len(), if tests.len() == 1 { "" } else { "s" })
+  }
+  for (name, spec) in &tests {
+    let (event, error) = match &spec.result {
+      Some(Ok(_)) => {
+        passed += 1;
+        ("ok", None)
+      },
+      Some(Err(msg)) => {
+        failed += 1;
+        failures.push((name, msg));
+        ("failed", Some(msg))
+      },
+      None => {
+        ignored += 1;
+        ("ignored", None)
+      }
+    };
+    match format {
+      HarnessFormat::Pretty => outln!("test {} ... {}", name, match event {
+        "ok" => green("ok"),
+        "failed" => red("FAILED"),
+        _ => yellow("ignored")
+      }),
+      HarnessFormat::Terse => out!("{}", match event {
+        "ok" => ".".to_string(),
+        "failed" => red("F"),
+        _ => yellow("i")
+      }),
+      HarnessFormat::Json => {
+        outln!("{}", json!({ "type": "test", "event": "started", "name": name }));
+        let mut result = json!({
+          "type": "test",
+          "name": name,
+          "event": event,
+          "exec_time": spec.duration.as_secs_f64()
+        });
+        if let Some(msg) = error {
+          result["stdout"] = json!(msg);
+        }
+        outln!("{}", result);
+      }
+    }
+  }
+  let exec_time = suite.total_duration.as_secs_f64();
+  if format == HarnessFormat::Json {
+    outln!("{}", json!({
+      "type": "suite",
+      "event": if failed == 0 { "ok" } else { "failed" },
+      "passed": passed,
+      "failed": failed,
+      "ignored": ignored,
+      "measured": 0,
+      "filtered_out": suite.filtered_out,
+      "exec_time": exec_time
+    }));
+    return;
+  }
+  if format == HarnessFormat::Terse {
+    outln!();
+  }
+  if !failures.is_empty() {
+    outln!("\nfailures:\n");
+    for (name, msg) in &failures {
+      outln!("---- {} stdout ----\n{}\n", name, msg);
+    }
+    outln!("\nfailures:");
+    for (name, _) in &failures {
+      outln!("    {}", name);
+    }
+  }
+  outln!("\ntest result: {}. {} passed; {} failed; {} ignored; 0 measured; {} filtered out; finished in {:.2}s\n",
+    if failed == 0 { green("ok") } else { red("FAILED") }, passed, failed, ignored, suite.filtered_out, exec_time);
+}
+
+// Durations are reported in the root suite's unit, which is passed down.
+fn get_stats_for_json<T>(suite: &Suite<T>, stats: &mut JsonReport, prefix: String, path: Vec<String>, duration_type: DurationType) {
+  for spec in &suite.context.specs {
+    let mut spec_path = path.clone();
+    spec_path.push(spec.name.to_string());
     let mut spec_stat = JsonSpecReport {
       title: spec.name.to_string(),
       full_title: format!("{} {}", prefix, spec.name),
-      duration: spec.duration,
+      path: spec_path,
+      status: spec_status(spec),
+      duration: duration_type.value(spec.duration),
+      duration_unit: duration_type.unit(),
       error: None,
-      attempts: spec.context.attempts
+      attempts: spec.context.attempts,
+      flaky: spec.flaky(),
+      attempt_history: attempt_history(spec, duration_type),
+      repeat: repeat_report(spec, duration_type),
+      bench: bench_report(spec, duration_type),
+      baseline: baseline_report(spec, duration_type),
+      case: spec.case.clone()
     };
     if let Some(result) = &spec.result {
       if let Err(msg) = result {
@@ -363,6 +776,9 @@ fn get_stats_for_json<T>(suite: &Suite<T>, stats: &mut JsonReport, prefix: Strin
         stats.stats.failing += 1;
         stats.failing.push(spec_stat.copy());
       } else {
+        if spec_stat.baseline.as_ref().is_some_and(|baseline| baseline.regressed) {
+          stats.regressions.push(spec_stat.copy());
+        }
         stats.passing.push(spec_stat.copy());
         stats.stats.passing += 1;
       }
@@ -370,13 +786,546 @@ fn get_stats_for_json<T>(suite: &Suite<T>, stats: &mut JsonReport, prefix: Strin
       stats.pending.push(spec_stat.copy());
       stats.stats.pending += 1;
     }
-    stats.stats.duration += spec.duration;
+    stats.stats.tests += 1;
     stats.tests.push(spec_stat);
   }
   for child_suite in &suite.context.suites {
     stats.stats.suites += 1;
-    get_stats_for_json(child_suite, stats, format!("{} {}", prefix, child_suite.name));
+    let mut child_path = path.clone();
+    child_path.push(child_suite.name.to_string());
+    get_stats_for_json(child_suite, stats, format!("{} {}", prefix, child_suite.name), child_path, duration_type);
+  }
+}
+
+fn get_json_tree<T>(suite: &Suite<T>, prefix: String, path: Vec<String>, duration_type: DurationType) -> JsonSuiteReport {
+  let mut specs = vec![];
+  for spec in &suite.context.specs {
+    let mut spec_path = path.clone();
+    spec_path.push(spec.name.to_string());
+    specs.push(JsonSpecReport {
+      title: spec.name.to_string(),
+      full_title: format!("{} {}", prefix, spec.name),
+      path: spec_path,
+      status: spec_status(spec),
+      duration: duration_type.value(spec.duration),
+      duration_unit: duration_type.unit(),
+      error: match &spec.result {
+        Some(Err(msg)) => Some(msg.to_string()),
+        _ => None
+      },
+      attempts: spec.context.attempts,
+      flaky: spec.flaky(),
+      attempt_history: attempt_history(spec, duration_type),
+      repeat: repeat_report(spec, duration_type),
+      bench: bench_report(spec, duration_type),
+      baseline: baseline_report(spec, duration_type),
+      case: spec.case.clone()
+    });
+  }
+  let mut suites = vec![];
+  for child_suite in &suite.context.suites {
+    let mut child_path = path.clone();
+    child_path.push(child_suite.name.to_string());
+    suites.push(get_json_tree(child_suite, format!("{} {}", prefix, child_suite.name), child_path, duration_type));
+  }
+  let hook_counts = suite.context.hook_counts;
+  JsonSuiteReport {
+    title: suite.name.to_string(),
+    path,
+    skipped: suite.context.skip_,
+    start: time_or_none(&suite.start_time),
+    end: time_or_none(&suite.end_time),
+    stats: JsonSuiteStats {
+      tests: suite.context.specs.len() as u32,
+      passing: suite.context.passed,
+      pending: suite.context.ignored,
+      failing: suite.context.failed,
+      duration: duration_type.value(suite.suite_duration),
+      total_duration: duration_type.value(suite.total_duration),
+      duration_unit: duration_type.unit()
+    },
+    hooks: JsonHookReport {
+      before_all: hook_counts.before_all,
+      before_each: hook_counts.before_each,
+      after_each: hook_counts.after_each,
+      after_all: hook_counts.after_all
+    },
+    specs,
+    suites,
+    durations: None,
+    snapshots: None
+  }
+}
+
+fn snapshot_report<T>(suite: &Suite<T>) -> Option<JsonSnapshotReport> {
+  let snapshots = &suite.snapshots;
+  if snapshots.is_empty() {
+    return None;
+  }
+  let to_strings = |paths: &Vec<PathBuf>| paths.iter().map(|path| path.display().to_string()).collect();
+  Some(JsonSnapshotReport {
+    matched: snapshots.matched,
+    failed: snapshots.failed,
+    written: to_strings(&snapshots.written),
+    updated: to_strings(&snapshots.updated),
+    obsolete: to_strings(&snapshots.obsolete)
+  })
+}
+
+fn print_snapshot_summary<T>(suite: &Suite<T>) {
+  let snapshots = &suite.snapshots;
+  if snapshots.is_empty() {
+    return;
+  }
+  outln!();
+  outln!("Snapshots {} {} {} {} {}",
+    green(format!("{} passed", snapshots.matched)),
+    red(format!("{} failed", snapshots.failed)),
+    cyan(format!("{} written", snapshots.written.len())),
+    cyan(format!("{} updated", snapshots.updated.len())),
+    yellow(format!("{} obsolete", snapshots.obsolete.len())));
+  for path in &snapshots.obsolete {
+    outln!("  {}", dim(format!("obsolete: {}", path.display())));
+  }
+}
+
+fn collect_durations<T>(suite: &Suite<T>, specs: &mut Vec<SlowEntry>, suites: &mut Vec<SlowEntry>) {
+  for spec in &suite.context.specs {
+    if spec.result.is_some() {
+      let mut path = suite.path.clone();
+      path.push(spec.name.to_string());
+      specs.push(SlowEntry { path, duration: spec.duration, share: 0.0 });
+    }
+  }
+  if !suite.context.specs.is_empty() {
+    suites.push(SlowEntry { path: suite.path.clone(), duration: suite.suite_duration, share: 0.0 });
+  }
+  for child_suite in &suite.context.suites {
+    collect_durations(child_suite, specs, suites);
+  }
+}
+
+// Nearest-rank percentile of an ascending list of durations.
+fn percentile(sorted: &[Duration], percent: u32) -> Duration {
+  if sorted.is_empty() {
+    return Duration::ZERO;
+  }
+  let rank = (percent as usize * sorted.len()).div_ceil(100);
+  sorted[rank.max(1) - 1]
+}
+
+fn duration_summary<T>(suite: &Suite<T>) -> Option<DurationSummary> {
+  let count = suite.slowest?;
+  let mut specs = vec![];
+  let mut suites = vec![];
+  collect_durations(suite, &mut specs, &mut suites);
+  let mut sorted: Vec<Duration> = specs.iter().map(|entry| entry.duration).collect();
+  sorted.sort_unstable();
+  let percentiles = [50, 90, 95, 99].map(|percent| (percent, percentile(&sorted, percent)));
+  let total = suite.total_duration;
+  let slowest = |mut entries: Vec<SlowEntry>| {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.duration));
+    entries.truncate(count);
+    for entry in &mut entries {
+      entry.share = if total > Duration::ZERO { entry.duration.as_secs_f64() / total.as_secs_f64() * 100.0 } else { 0.0 };
+    }
+    entries
+  };
+  Some(DurationSummary {
+    slowest_specs: slowest(specs),
+    slowest_suites: slowest(suites),
+    percentiles
+  })
+}
+
+fn print_duration_summary<T>(suite: &Suite<T>) {
+  if let Some(summary) = duration_summary(suite) {
+    for (title, entries) in [("Slowest specs", &summary.slowest_specs), ("Slowest suites", &summary.slowest_suites)] {
+      outln!();
+      outln!("{}", title);
+      for (i, entry) in entries.iter().enumerate() {
+        outln!("  {}. {} {} {}",
+          i + 1,
+          entry.path.join(" › "),
+          yellow(format_duration(suite.duration_type, entry.duration).to_string()),
+          dim(format!("{:.1}%", entry.share)));
+      }
+    }
+    outln!();
+    let percentiles: Vec<String> = summary.percentiles.iter()
+      .map(|(percent, duration)| format!("p{} {}", percent, format_duration(suite.duration_type, *duration)))
+      .collect();
+    outln!("{}", dim(percentiles.join("  ")));
+  }
+}
+
+fn duration_report<T>(suite: &Suite<T>) -> Option<JsonDurationReport> {
+  let to_json = |entries: Vec<SlowEntry>| entries.into_iter().map(|entry| JsonSlowReport {
+    title: entry.path.last().cloned().unwrap_or_default(),
+    path: entry.path,
+    duration: suite.duration_type.value(entry.duration),
+    share: entry.share
+  }).collect();
+  duration_summary(suite).map(|summary| {
+    let [p50, p90, p95, p99] = summary.percentiles.map(|(_, duration)| suite.duration_type.value(duration));
+    JsonDurationReport {
+      slowest_specs: to_json(summary.slowest_specs),
+      slowest_suites: to_json(summary.slowest_suites),
+      percentiles: JsonPercentiles { p50, p90, p95, p99 },
+      duration_unit: suite.duration_type.unit()
+    }
+  })
+}
+
+fn attempt_history<T>(spec: &Spec<T>, duration_type: DurationType) -> Vec<JsonAttemptReport> {
+  spec.history.iter().enumerate().map(|(i, attempt)| JsonAttemptReport {
+    attempt: i as u32 + 1,
+    error: attempt.error.clone(),
+    duration: duration_type.value(attempt.duration)
+  }).collect()
+}
+
+fn repeat_report<T>(spec: &Spec<T>, duration_type: DurationType) -> Option<JsonRepeatReport> {
+  spec.repeat.as_ref().map(|stats| JsonRepeatReport {
+    iterations: stats.iterations,
+    passed: stats.passed,
+    failed: stats.failed,
+    pass_rate: stats.pass_rate(),
+    min_duration: duration_type.value(stats.min_duration),
+    mean_duration: duration_type.value(stats.mean_duration),
+    max_duration: duration_type.value(stats.max_duration),
+    first_failure: stats.first_failure
+  })
+}
+
+fn bench_report<T>(spec: &Spec<T>, duration_type: DurationType) -> Option<JsonBenchReport> {
+  spec.bench.as_ref().map(|stats| JsonBenchReport {
+    iterations: stats.iterations,
+    mean: duration_type.value(stats.mean),
+    median: duration_type.value(stats.median),
+    std_dev: duration_type.value(stats.std_dev),
+    min: duration_type.value(stats.min),
+    max: duration_type.value(stats.max),
+    throughput: stats.throughput
+  })
+}
+
+fn baseline_report<T>(spec: &Spec<T>, duration_type: DurationType) -> Option<JsonBaselineReport> {
+  spec.baseline.map(|comparison| JsonBaselineReport {
+    duration: duration_type.value(comparison.baseline),
+    change: comparison.change,
+    regressed: comparison.regressed
+  })
+}
+
+fn regression_note<T>(spec: &Spec<T>, duration_type: DurationType) -> Option<String> {
+  match spec.baseline {
+    Some(comparison) if comparison.regressed => Some(format!("{:+.1}% vs {}",
+      comparison.change, format_duration(duration_type, comparison.baseline))),
+    _ => None
+  }
+}
+
+fn count_regressions<T>(suite: &Suite<T>) -> u32 {
+  let regressed = suite.context.specs.iter()
+    .filter(|spec| spec.baseline.is_some_and(|comparison| comparison.regressed))
+    .count() as u32;
+  regressed + suite.context.suites.iter().map(count_regressions).sum::<u32>()
+}
+
+fn print_baseline_summary<T>(suite: &Suite<T>) {
+  if let Some(comparison) = suite.baseline_comparison {
+    let regressions = count_regressions(suite);
+    outln!();
+    if regressions > 0 {
+      outln!("{}", red(format!("{} spec{} regressed by more than {}% and {} against the baseline",
+        regressions, get_suffix(regressions), suite.regression_threshold, suite.duration_type.format(suite.regression_min_delta))));
+    }
+    outln!("{}", dim(format!("total {} vs baseline {}, {:+.1}%",
+      format_duration(suite.duration_type, suite.total_duration),
+      format_duration(suite.duration_type, comparison.baseline),
+      comparison.change)));
+  }
+}
+
+fn collect_benches<'a, T>(suite: &'a Suite<T>, benches: &mut Vec<(String, &'a Spec<T>)>) {
+  for spec in &suite.context.specs {
+    if spec.bench.is_some() {
+      benches.push((format!("{} › {}", suite.path.join(" › "), spec.name), spec));
+    }
+  }
+  for child_suite in &suite.context.suites {
+    collect_benches(child_suite, benches);
+  }
+}
+
+fn print_bench_summary<T>(suite: &Suite<T>) {
+  let mut benches = vec![];
+  collect_benches(suite, &mut benches);
+  if benches.is_empty() {
+    return;
+  }
+  outln!();
+  outln!("Benchmarks");
+  for (name, spec) in benches {
+    if let Some(stats) = &spec.bench {
+      outln!("  {} {} {}",
+        name,
+        cyan(format!("mean {} median {} ± {}",
+          format_duration(suite.duration_type, stats.mean),
+          format_duration(suite.duration_type, stats.median),
+          format_duration(suite.duration_type, stats.std_dev))),
+        dim(format!("{:.0} ops/sec over {} runs", stats.throughput, stats.iterations)));
+    }
+  }
+}
+
+fn repeat_note<T>(spec: &Spec<T>, duration_type: DurationType) -> Option<String> {
+  spec.repeat.as_ref().map(|stats| format!("{} of {} passed ({:.0}%), min {} mean {} max {}",
+    stats.passed,
+    stats.iterations,
+    stats.pass_rate() * 100.0,
+    format_duration(duration_type, stats.min_duration),
+    format_duration(duration_type, stats.mean_duration),
+    format_duration(duration_type, stats.max_duration)))
+}
+
+fn flaky_note<T>(spec: &Spec<T>) -> Option<String> {
+  if spec.flaky() {
+    Some(format!("flaky, passed on attempt {}", spec.history.len()))
+  } else {
+    None
+  }
+}
+
+fn spec_status<T>(spec: &Spec<T>) -> &'static str {
+  match &spec.result {
+    Some(Ok(_)) => "passed",
+    Some(Err(_)) => "failed",
+    None => "pending"
+  }
+}
+
+fn time_or_none(time: &str) -> Option<String> {
+  if time.is_empty() {
+    None
+  } else {
+    Some(time.to_string())
+  }
+}
+
+fn print_json<S: Serialize>(report: &S, pretty: bool) {
+  let json = if pretty {
+    to_string_pretty(report)
+  } else {
+    to_string(report)
+  };
+  match json {
+    Ok(json) => outln!("{}", json),
+    Err(_) => outln!("Could not print out json result")
+  }
+}
+
+fn format_duration(duration_type: DurationType, duration: Duration) -> String {
+  format!("({})", duration_type.format(duration))
+}
+
+// Names go in table cells and headings, so line breaks become spaces and
+// punctuation markdown or html would act on is backslash-escaped.
+fn escape_markdown(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\n' | '\r' => escaped.push(' '),
+      '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '|' | '#' => {
+        escaped.push('\\');
+        escaped.push(c);
+      },
+      _ => escaped.push(c)
+    }
   }
+  escaped
+}
+
+// A code fence longer than any run of backticks in the text, so the text
+// can't close it.
+fn code_fence(text: &str) -> String {
+  let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+  "`".repeat(longest.max(2) + 1)
+}
+
+fn get_markdown<T>(suite: &Suite<T>, body: &mut String, stats: &mut MinReporterStats, path: String, depth: u32) {
+  let level = "#".repeat((depth as usize + 2).min(6));
+  body.push_str(&format!("{} {}\n\n", level, escape_markdown(&suite.name)));
+  if !suite.context.specs.is_empty() {
+    body.push_str("| Result | Spec | Duration |\n| :---: | --- | ---: |\n");
+  }
+  for spec in &suite.context.specs {
+    let name = escape_markdown(&spec.name);
+    match &spec.result {
+      Some(Ok(_)) => {
+        let duration = format_duration(suite.duration_type, spec.duration);
+        let duration = match spec.context.speed_result {
+          Speed::Slow => format!("**{} slow**", duration),
+          Speed::OnTime => format!("_{}_", duration),
+          Speed::Fast => duration.to_string()
+        };
+        let flaky = match flaky_note(spec) {
+          Some(note) => format!(" _({})_", note),
+          None => String::new()
+        };
+        let repeat = match repeat_note(spec, suite.duration_type) {
+          Some(note) => format!(" _[{}]_", note),
+          None => String::new()
+        };
+        let regression = match regression_note(spec, suite.duration_type) {
+          Some(note) => format!(" **(regressed {})**", note),
+          None => String::new()
+        };
+        body.push_str(&format!("| ✅ | {}{}{}{} | {} |\n", name, flaky, repeat, regression, duration));
+        stats.passed += 1;
+      },
+      Some(Err(msg)) => {
+        let duration = format_duration(suite.duration_type, spec.duration);
+        body.push_str(&format!("| ❌ | {} | {} |\n", name, duration));
+        stats.failed += 1;
+        let fence = code_fence(msg);
+        stats.error_lines.push(format!("{}. **{} › {}**\n\n   {}text\n   {}\n   {}\n",
+          stats.failed, escape_markdown(&path), name, fence, msg.replace('\n', "\n   "), fence));
+      },
+      None => {
+        body.push_str(&format!("| ⏸️ | {} | |\n", name));
+        stats.pending += 1;
+      }
+    }
+  }
+  if !suite.context.specs.is_empty() {
+    body.push('\n');
+  }
+  for child_suite in &suite.context.suites {
+    get_markdown(child_suite, body, stats, format!("{} › {}", path, child_suite.name), depth + 1);
+  }
+}
+
+fn markdown_report<T>(suite: &Suite<T>) -> String {
+  let mut stats = MinReporterStats {
+    passed: 0,
+    failed: 0,
+    pending: 0,
+    error_lines: vec![]
+  };
+  let mut body = String::new();
+  get_markdown(suite, &mut body, &mut stats, suite.name.to_string(), 0);
+  let mut document = format!("# Lab Results: {}\n\n", escape_markdown(&suite.name));
+  document.push_str(&format!("✅ {} passing · ❌ {} failing · ⏸️ {} pending · ⏱️ {}\n\n",
+    stats.passed, stats.failed, stats.pending, format_duration(suite.duration_type, suite.total_duration)));
+  document.push_str(&body);
+  if !stats.error_lines.is_empty() {
+    document.push_str("## Failures\n\n");
+    for line in &stats.error_lines {
+      document.push_str(line);
+      document.push('\n');
+    }
+  }
+  document
+}
+
+fn escape_html(text: &str) -> String {
+  text.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&#39;")
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+details.suite{margin:.25em 0 .25em 1.25em}\
+summary{cursor:pointer;font-weight:bold}\
+ul{list-style:none;margin:.25em 0;padding-left:1.25em}\
+.counts{font-weight:normal;color:#666}\
+.passed::before{content:'✓ ';color:#2a7a2a}\
+.failed,.failed::before{color:#b52a2a}\
+.failed::before{content:'✖ '}\
+.pending{color:#888}\
+.pending::before{content:'- '}\
+.duration{color:#666}\
+.ontime .duration{color:#b58a00}\
+.slow .duration{color:#b52a2a;font-weight:bold}\
+.flaky{color:#b58a00}\
+.repeat{color:#666}\
+.regressed{color:#b52a2a}\
+pre.error{background:#fbeaea;padding:.5em;white-space:pre-wrap}";
+
+fn get_html<T>(suite: &Suite<T>, body: &mut String, stats: &mut MinReporterStats) {
+  let (passed, failed, pending) = (stats.passed, stats.failed, stats.pending);
+  let mut items = String::new();
+  for spec in &suite.context.specs {
+    let name = escape_html(&spec.name);
+    match &spec.result {
+      Some(Ok(_)) => {
+        let speed = match spec.context.speed_result {
+          Speed::Fast => "fast",
+          Speed::OnTime => "ontime",
+          Speed::Slow => "slow"
+        };
+        let flaky = match flaky_note(spec) {
+          Some(note) => format!(" <span class=\"flaky\">{}</span>", note),
+          None => String::new()
+        };
+        let repeat = match repeat_note(spec, suite.duration_type) {
+          Some(note) => format!(" <span class=\"repeat\">{}</span>", note),
+          None => String::new()
+        };
+        let regression = match regression_note(spec, suite.duration_type) {
+          Some(note) => format!(" <span class=\"regressed\">regressed {}</span>", note),
+          None => String::new()
+        };
+        items.push_str(&format!("<li class=\"spec passed {}\">{} <span class=\"duration\">{}</span>{}{}{}</li>\n",
+          speed, name, format_duration(suite.duration_type, spec.duration), flaky, repeat, regression));
+        stats.passed += 1;
+      },
+      Some(Err(msg)) => {
+        items.push_str(&format!("<li class=\"spec failed\">{} <span class=\"duration\">{}</span><pre class=\"error\">{}</pre></li>\n",
+          name, format_duration(suite.duration_type, spec.duration), escape_html(msg)));
+        stats.failed += 1;
+      },
+      None => {
+        items.push_str(&format!("<li class=\"spec pending\">{}</li>\n", name));
+        stats.pending += 1;
+      }
+    }
+  }
+  let mut children = String::new();
+  for child_suite in &suite.context.suites {
+    get_html(child_suite, &mut children, stats);
+  }
+  body.push_str(&format!("<details class=\"suite\"{}><summary>{} <span class=\"counts\">{} passing, {} failing, {} pending {}</span></summary>\n",
+    if suite.context.fail { " open" } else { "" },
+    escape_html(&suite.name),
+    stats.passed - passed,
+    stats.failed - failed,
+    stats.pending - pending,
+    format_duration(suite.duration_type, suite.total_duration)));
+  if !items.is_empty() {
+    body.push_str(&format!("<ul>\n{}</ul>\n", items));
+  }
+  body.push_str(&children);
+  body.push_str("</details>\n");
+}
+
+fn html_report<T>(suite: &Suite<T>) -> String {
+  let mut stats = MinReporterStats {
+    passed: 0,
+    failed: 0,
+    pending: 0,
+    error_lines: vec![]
+  };
+  let mut body = String::new();
+  get_html(suite, &mut body, &mut stats);
+  let title = escape_html(&suite.name);
+  format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Lab Results: {}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>Lab Results: {}</h1>\n<p><span class=\"passed\">{} passing</span> <span class=\"failed\">{} failing</span> <span class=\"pending\">{} pending</span> <span class=\"duration\">{}</span></p>\n{}</body>\n</html>",
+    title, HTML_STYLE, title, stats.passed, stats.failed, stats.pending,
+    format_duration(suite.duration_type, suite.total_duration), body)
 }
 
 fn get_suffix(n: u32) -> String {
@@ -387,8 +1336,29 @@ fn get_suffix(n: u32) -> String {
   }
 }
 
+// Writes the report to a file instead of stdout, unstyled unless colour
+// was asked for.
+pub fn report_to_file<T>(suite: &Suite<T>, path: &Path) -> Result<(), String> {
+  CAPTURED.with(|captured| *captured.borrow_mut() = Some(String::new()));
+  report_to_stdout(suite);
+  let report = CAPTURED.with(|captured| captured.borrow_mut().take()).unwrap_or_default();
+  if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+    fs::create_dir_all(dir)
+      .map_err(|err| format!("Could not create output directory {}: {}", dir.display(), err))?;
+  }
+  fs::write(path, report)
+    .map_err(|err| format!("Could not write report {}: {}", path.display(), err))
+}
+
 pub fn report_to_stdout<T>(suite: &Suite<T>) {
 
+  let enabled = match (suite.color, &suite.output) {
+    (ColorMode::Auto, Some(_)) => false,
+    (mode, _) => resolve_color(mode, &suite.reporter)
+  };
+  COLOR_ENABLED.with(|color| color.set(enabled));
+  let banner = banner_text(suite);
+
   match suite.reporter {
     Reporter::Spec => {
 
@@ -399,28 +1369,23 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
         error_lines: vec![]
       };
  
-      header();
+      header(&banner);
  
       get_lines_for_spec(suite, 0, &mut stats);
  
       if stats.failed == 0 {
  
-        let duration = match suite.duration_type {
-          DurationType::Nano => Duration::Nano(suite.total_duration),
-          DurationType::Micro => Duration::Micro(suite.total_duration),
-          DurationType::Mil => Duration::Mil(suite.total_duration),
-          DurationType::Sec => Duration::Sec(suite.total_duration)
-        };
+        let duration = format_duration(suite.duration_type, suite.total_duration);
  
-        println!("{}{} {}",
+        outln!("{}{} {}",
           green("✓"), 
           green(format!(" {} test{} completed", stats.passed, get_suffix(stats.passed))),
-          dim(format!("{}", duration.to_string()))
+          dim(duration.to_string())
         );
   
       } else {
  
-        println!(" {}{}",
+        outln!(" {}{}",
           red(format!("✖ {} of {} test{} failed", 
             stats.failed, 
             stats.passed + stats.failed, 
@@ -432,10 +1397,18 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
       }
  
       for spec_report_line in &stats.error_lines {
-        println!("{}", spec_report_line);
+        outln!("{}", spec_report_line);
       }
 
-      footer();
+      print_bench_summary(suite);
+
+      print_duration_summary(suite);
+
+      print_baseline_summary(suite);
+
+      print_snapshot_summary(suite);
+
+      footer(&banner);
 
     },
     Reporter::Min => {
@@ -461,36 +1434,31 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
 
       get_lines_for_min(suite, &mut stats, suite.name.to_string(), 0);
       
-      let duration = match suite.duration_type {
-        DurationType::Nano => Duration::Nano(suite.total_duration),
-        DurationType::Micro => Duration::Micro(suite.total_duration),
-        DurationType::Mil => Duration::Mil(suite.total_duration),
-        DurationType::Sec => Duration::Sec(suite.total_duration)
-      };
+      let duration = format_duration(suite.duration_type, suite.total_duration);
 
-      header();
+      header(&banner);
   
       if stats.passed > 0 {
-        println!("{} {}",
+        outln!("{} {}",
           green(format!("{} test{} complete", stats.passed, get_suffix(stats.passed))),
-          dim(format!("{}", duration.to_string()))
+          dim(duration.to_string())
         );
       }
       if stats.pending > 0 {
-        println!("{}", dim(format!("{} test{} pending", stats.pending, get_suffix(stats.pending))));
+        outln!("{}", dim(format!("{} test{} pending", stats.pending, get_suffix(stats.pending))));
       }
       if stats.failed > 0 {
-        println!("{}",
+        outln!("{}",
           red(format!("{} test{} failed", stats.failed, get_suffix(stats.failed)))
         );
-        print!("\n\n");
+        out!("\n\n");
         for line in &stats.error_lines {
-          println!("{}", line);
-          println!("");
+          outln!("{}", line);
+          outln!();
         }
       }
 
-      footer();
+      footer(&banner);
 
    },
     Reporter::Dot => {
@@ -501,15 +1469,15 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
         dots: vec![]
       };
       get_dots(suite, &mut stats);
-      header();
+      header(&banner);
       for line in &stats.dots {
-        print!("{}", line);
+        out!("{}", line);
       }
-      print!("\n\n");
-      println!("{}", green(format!("{} passing", stats.passed)));
-      println!("{}", cyan(format!("{} pending", stats.pending)));
-      println!("{}", red(format!("{} failed", stats.failed)));
-      footer();
+      out!("\n\n");
+      outln!("{}", green(format!("{} passing", stats.passed)));
+      outln!("{}", cyan(format!("{} pending", stats.pending)));
+      outln!("{}", red(format!("{} failed", stats.failed)));
+      footer(&banner);
     },
     Reporter::List => {
       let mut stats = MinReporterStats {
@@ -518,30 +1486,28 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
         pending: 0,
         error_lines: vec![]
       };
-      let duration = match suite.duration_type {
-        DurationType::Nano => Duration::Nano(suite.total_duration),
-        DurationType::Micro => Duration::Micro(suite.total_duration),
-        DurationType::Mil => Duration::Mil(suite.total_duration),
-        DurationType::Sec => Duration::Sec(suite.total_duration)
-      };
-      header();
+      let duration = format_duration(suite.duration_type, suite.total_duration);
+      header(&banner);
       get_list(suite, &mut stats, suite.name.to_string());
-      print!("\n");
-      println!("{}", green(format!("{} passing {}", stats.passed, duration.to_string())));
-      println!("{}", cyan(format!("{} pending", stats.pending)));
-      println!("{}", red(format!("{} failed", stats.failed)));
-      footer();
+      outln!();
+      outln!("{}", green(format!("{} passing {}", stats.passed, duration)));
+      outln!("{}", cyan(format!("{} pending", stats.pending)));
+      outln!("{}", red(format!("{} failed", stats.failed)));
+      print_bench_summary(suite);
+      print_duration_summary(suite);
+      print_snapshot_summary(suite);
+      footer(&banner);
     },
     Reporter::Tap => {
       let mut lines = vec![];
       let mut count = 0;
       get_tap_list(suite, &mut lines, &mut count, suite.name.to_string());
-      header();
-      println!("{}", green(format!("1..{}", count)));
+      header(&banner);
+      outln!("{}", green(format!("1..{}", count)));
       for line in &lines {
-        println!("{}", line);
+        outln!("{}", line);
       }
-      footer();
+      footer(&banner);
     },
     Reporter::Rust => {
 
@@ -557,25 +1523,25 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
         error_lines: vec![]
       };
       let count = get_count(suite);
-      header();
-      println!("Running {} test{}", count, get_suffix(count));
-      print!("\n\n");
+      header(&banner);
+      outln!("Running {} test{}", count, get_suffix(count));
+      out!("\n\n");
       get_list_for_rust(suite, &mut stats, suite.name.to_case(Case::Snake));
-      print!("\n");
-      let passed = green(&format!("{} passed", stats.passed));
-      let ignored = cyan(&format!("{} ignored", stats.pending));      
+      outln!();
+      let passed = green(format!("{} passed", stats.passed));
+      let ignored = cyan(format!("{} ignored", stats.pending));      
       if stats.failed == 0 {
-        println!("test result: {}. {}; 0 failed; {}; 0 measured; 0 filtered out", green("ok"), passed, ignored);
+        outln!("test result: {}. {}; 0 failed; {}; 0 measured; 0 filtered out", green("ok"), passed, ignored);
       } else {
-        let failed = red(&format!("{} failed", stats.failed));
-        println!("{}", red("failures:"));
+        let failed = red(format!("{} failed", stats.failed));
+        outln!("{}", red("failures:"));
         for line in &stats.error_lines {
-          println!("    {}", red(line));            
+          outln!("    {}", red(line));            
         }
-        print!("\n");
-        println!("test result: {}. {}; {}; {}; 0 measured; 0 filtered out", red("FAILED"), passed, failed, ignored);
+        outln!();
+        outln!("test result: {}. {}; {}; {}; 0 measured; 0 filtered out", red("FAILED"), passed, failed, ignored);
       }
-      footer();
+      footer(&banner);
     },
     Reporter::Json(pretty) => {
       let mut json_report = JsonReport {
@@ -587,29 +1553,41 @@ pub fn report_to_stdout<T>(suite: &Suite<T>) {
           failing: 0,
           start: suite.start_time.to_string(),
           end: suite.end_time.to_string(),
-          duration: 0
+          duration: suite.duration_type.value(suite.total_duration),
+          duration_unit: suite.duration_type.unit()
         },
         tests: vec![],
         passing: vec![],
         pending: vec![],
-        failing: vec![]
+        failing: vec![],
+        regressions: vec![],
+        durations: duration_report(suite),
+        snapshots: snapshot_report(suite)
       };
-      get_stats_for_json(suite, &mut json_report, suite.name.to_string());
-      header();
-      if pretty {
-        if let Ok(json) = to_string_pretty(&json_report) {
-          println!("{}", json);
-        } else {
-          println!("Could not print out json result");
-        }          
-      } else {
-        if let Ok(json) = to_string(&json_report) {
-          println!("{}", json);
-        } else {
-          println!("Could not print out json result");
-        }
-      }
-      footer();
-    }
+      get_stats_for_json(suite, &mut json_report, suite.name.to_string(), vec![suite.name.to_string()], suite.duration_type);
+      header(&banner);
+      print_json(&json_report, pretty);
+      footer(&banner);
+    },
+    Reporter::JsonTree(pretty) => {
+      let mut json_report = get_json_tree(suite, suite.name.to_string(), vec![suite.name.to_string()], suite.duration_type);
+      json_report.durations = duration_report(suite);
+      json_report.snapshots = snapshot_report(suite);
+      header(&banner);
+      print_json(&json_report, pretty);
+      footer(&banner);
+    },
+    Reporter::Markdown => {
+      header(&banner);
+      outln!("{}", markdown_report(suite));
+      footer(&banner);
+    },
+    Reporter::Html => {
+      header(&banner);
+      outln!("{}", html_report(suite));
+      footer(&banner);
+    },
+    // cargo and IDEs parse this output, so it never gets a banner.
+    Reporter::Libtest(format) => print_libtest(suite, format)
   }    
 }
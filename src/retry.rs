@@ -0,0 +1,72 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Backoff {
+  Immediate,
+  Fixed(Duration),
+  Exponential { initial: Duration, factor: u32, max: Option<Duration> }
+}
+
+pub type RetryPredicate = Rc<dyn Fn(&str) -> bool>;
+
+#[derive(Clone)]
+pub struct RetryPolicy {
+  pub retries: u32,
+  pub backoff: Backoff,
+  pub retry_if: Option<RetryPredicate>
+}
+impl RetryPolicy {
+  pub fn new(retries: u32) -> RetryPolicy {
+    RetryPolicy {
+      retries,
+      backoff: Backoff::Immediate,
+      retry_if: None
+    }
+  }
+  pub fn fixed(mut self, delay: Duration) -> Self {
+    self.backoff = Backoff::Fixed(delay);
+    self
+  }
+  pub fn exponential(mut self, initial: Duration, factor: u32) -> Self {
+    self.backoff = Backoff::Exponential { initial, factor, max: None };
+    self
+  }
+  pub fn max_delay(mut self, max_delay: Duration) -> Self {
+    if let Backoff::Exponential { initial, factor, .. } = self.backoff {
+      self.backoff = Backoff::Exponential { initial, factor, max: Some(max_delay) };
+    }
+    self
+  }
+  pub fn retry_if<P: Fn(&str) -> bool + 'static>(mut self, predicate: P) -> Self {
+    self.retry_if = Some(Rc::new(predicate));
+    self
+  }
+  pub fn should_retry(&self, error: &str) -> bool {
+    match &self.retry_if {
+      Some(predicate) => (predicate)(error),
+      None => true
+    }
+  }
+  // The delay to wait after the given (1-based) failed attempt.
+  pub fn delay(&self, attempt: u32) -> Duration {
+    match self.backoff {
+      Backoff::Immediate => Duration::from_secs(0),
+      Backoff::Fixed(delay) => delay,
+      Backoff::Exponential { initial, factor, max } => {
+        let exponent = attempt.saturating_sub(1);
+        let delay = initial.checked_mul(factor.saturating_pow(exponent)).unwrap_or(Duration::MAX);
+        match max {
+          Some(max) if delay > max => max,
+          _ => delay
+        }
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Attempt {
+  pub error: Option<String>,
+  pub duration: Duration
+}
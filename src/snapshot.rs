@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::LabResult;
+
+// Set to overwrite snapshots that no longer match instead of failing.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "LAB_UPDATE_SNAPSHOTS";
+
+// What happened to the snapshot files during a run.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotSummary {
+  pub matched: u32,
+  pub failed: u32,
+  pub written: Vec<PathBuf>,
+  pub updated: Vec<PathBuf>,
+  pub obsolete: Vec<PathBuf>,
+  touched: HashSet<PathBuf>
+}
+impl SnapshotSummary {
+  pub fn is_empty(&self) -> bool {
+    self.matched == 0 && self.failed == 0 && self.written.is_empty()
+      && self.updated.is_empty() && self.obsolete.is_empty()
+  }
+  pub(crate) fn touched(&self, path: &Path) -> bool {
+    self.touched.contains(path)
+  }
+}
+
+struct ActiveSpec {
+  dir: PathBuf,
+  spec: String
+}
+
+thread_local! {
+  static ACTIVE_SPEC: RefCell<Option<ActiveSpec>> = const { RefCell::new(None) };
+  static SUMMARY: RefCell<SnapshotSummary> = RefCell::new(SnapshotSummary::default());
+}
+
+// Snapshots live in one directory per suite, nested like the suites, with
+// one "<spec>@<name>.snap" file per snapshot.
+pub fn slug(name: &str) -> String {
+  let mut slug = String::new();
+  for c in name.trim().chars() {
+    if c.is_alphanumeric() {
+      slug.extend(c.to_lowercase());
+    } else if !slug.ends_with('_') {
+      slug.push('_');
+    }
+  }
+  let slug = slug.trim_matches('_');
+  if slug.is_empty() { "_".to_string() } else { slug.to_string() }
+}
+
+pub fn suite_dir(root: &Path, suite_path: &[String]) -> PathBuf {
+  suite_path.iter().fold(root.to_path_buf(), |dir, name| dir.join(slug(name)))
+}
+
+pub fn default_dir() -> PathBuf {
+  match env::var_os("CARGO_MANIFEST_DIR") {
+    Some(dir) => PathBuf::from(dir).join("snapshots"),
+    None => PathBuf::from("snapshots")
+  }
+}
+
+pub(crate) fn reset() {
+  SUMMARY.with(|summary| *summary.borrow_mut() = SnapshotSummary::default());
+}
+
+pub(crate) fn take_summary() -> SnapshotSummary {
+  SUMMARY.with(|summary| summary.replace(SnapshotSummary::default()))
+}
+
+pub(crate) fn begin_spec(dir: PathBuf, spec: &str) {
+  ACTIVE_SPEC.with(|active| *active.borrow_mut() = Some(ActiveSpec { dir, spec: spec.to_string() }));
+}
+
+pub(crate) fn end_spec() {
+  ACTIVE_SPEC.with(|active| *active.borrow_mut() = None);
+}
+
+fn update_requested() -> bool {
+  env::var_os(UPDATE_SNAPSHOTS_VAR).is_some_and(|value| value != "0")
+}
+
+pub fn assert_snapshot(name: &str, actual: String) -> LabResult {
+  let path = ACTIVE_SPEC.with(|active| {
+    active.borrow().as_ref().map(|active| active.dir.join(format!("{}@{}.snap", slug(&active.spec), slug(name))))
+  }).ok_or_else(|| format!("Expected snapshot \"{}\" to be checked inside a running spec", name))?;
+  SUMMARY.with(|summary| {
+    let mut summary = summary.borrow_mut();
+    summary.touched.insert(path.clone());
+    match fs::read_to_string(&path) {
+      Ok(expected) if expected == actual => {
+        summary.matched += 1;
+        Ok(())
+      },
+      Ok(expected) => {
+        if update_requested() {
+          write_snapshot(&path, &actual)?;
+          summary.updated.push(path);
+          Ok(())
+        } else {
+          summary.failed += 1;
+          Err(format!("Expected snapshot \"{}\" to match {}\n{}\nSet {}=1 to update it",
+            name, path.display(), diff(&expected, &actual), UPDATE_SNAPSHOTS_VAR))
+        }
+      },
+      Err(_) => {
+        write_snapshot(&path, &actual)?;
+        summary.written.push(path);
+        Ok(())
+      }
+    }
+  })
+}
+
+fn write_snapshot(path: &Path, contents: &str) -> LabResult {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)
+      .map_err(|err| format!("Could not create snapshot directory {}: {}", dir.display(), err))?;
+  }
+  fs::write(path, contents)
+    .map_err(|err| format!("Could not write snapshot {}: {}", path.display(), err))
+}
+
+pub(crate) fn snap_files(dir: &Path) -> Vec<PathBuf> {
+  let mut files = vec![];
+  if let Ok(entries) = fs::read_dir(dir) {
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if path.is_file() && path.extension().is_some_and(|extension| extension == "snap") {
+        files.push(path);
+      }
+    }
+  }
+  files.sort();
+  files
+}
+
+pub(crate) fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+  let mut dirs = vec![];
+  if let Ok(entries) = fs::read_dir(dir) {
+    for entry in entries.flatten() {
+      if entry.path().is_dir() {
+        dirs.push(entry.path());
+      }
+    }
+  }
+  dirs.sort();
+  dirs
+}
+
+pub(crate) fn all_snap_files(dir: &Path) -> Vec<PathBuf> {
+  let mut files = snap_files(dir);
+  for sub_dir in sub_dirs(dir) {
+    files.extend(all_snap_files(&sub_dir));
+  }
+  files
+}
+
+// The most cells the table of common subsequence lengths may have, so
+// large snapshots can't make a failing spec use quadratic memory.
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+// A line diff of the expected and actual snapshot, from the longest
+// common subsequence of their lines. Lines shared at the start and end are
+// left out of the table, and when the lines in between would still need a
+// larger one, they are shown as removed and then added.
+fn diff(expected: &str, actual: &str) -> String {
+  let old: Vec<&str> = expected.lines().collect();
+  let new: Vec<&str> = actual.lines().collect();
+  let prefix = old.iter().zip(&new).take_while(|(old, new)| old == new).count();
+  let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(old, new)| old == new).count();
+  let (old_changed, new_changed) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+  let mut lines = vec!["- expected".to_string(), "+ actual".to_string(), String::new()];
+  lines.extend(old[..prefix].iter().map(|line| format!("  {}", line)));
+  if (old_changed.len() + 1).saturating_mul(new_changed.len() + 1) > MAX_DIFF_CELLS {
+    lines.extend(old_changed.iter().map(|line| format!("- {}", line)));
+    lines.extend(new_changed.iter().map(|line| format!("+ {}", line)));
+  } else {
+    diff_lines(old_changed, new_changed, &mut lines);
+  }
+  lines.extend(old[old.len() - suffix..].iter().map(|line| format!("  {}", line)));
+  lines.join("\n")
+}
+
+fn diff_lines(old: &[&str], new: &[&str], lines: &mut Vec<String>) {
+  let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+  for i in (0..old.len()).rev() {
+    for j in (0..new.len()).rev() {
+      lengths[i][j] = if old[i] == new[j] {
+        lengths[i + 1][j + 1] + 1
+      } else {
+        lengths[i + 1][j].max(lengths[i][j + 1])
+      };
+    }
+  }
+  let (mut i, mut j) = (0, 0);
+  while i < old.len() || j < new.len() {
+    if i < old.len() && j < new.len() && old[i] == new[j] {
+      lines.push(format!("  {}", old[i]));
+      i += 1;
+      j += 1;
+    } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+      lines.push(format!("- {}", old[i]));
+      i += 1;
+    } else {
+      lines.push(format!("+ {}", new[j]));
+      j += 1;
+    }
+  }
+}
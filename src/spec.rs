@@ -1,23 +1,41 @@
 use std::{
   cell::RefCell,
   fmt::Display,
-  rc::Rc
+  rc::Rc,
+  time::Duration
 };
-use crate::suite::Speed;
-use crate::suite_context::State;
+use serde_json::Value;
+use crate::baseline::BaselineComparison;
+use crate::bench::{Bench, BenchStats};
+use crate::fixture::FixtureStore;
+use crate::prop::DEFAULT_CASES;
+use crate::repeat::{Repeat, RepeatStats};
+use crate::retry::{Attempt, RetryPolicy};
+use crate::suite::{SlowThreshold, Speed};
+use crate::state::State;
+
+pub type SpecHook<T> = Box<dyn Fn(&mut SpecContext<T>) -> Result<(), String> + 'static>;
 
 pub struct SpecOptions<T> {
   pub name: Option<String>,
   pub retries_: Option<u32>,
-  pub slow_: Option<u128>,
-  pub hook: Option<Box<dyn Fn(&mut SpecContext<T>) -> Result<(), String> + 'static>>,
+  pub retry_policy_: Option<RetryPolicy>,
+  pub repeat_: Option<Repeat>,
+  pub bench_: Option<Bench>,
+  pub slow_: Option<SlowThreshold>,
+  pub tags: Vec<String>,
+  pub hook: Option<SpecHook<T>>,
 }
 impl<T> SpecOptions<T> {
   pub fn new() -> SpecOptions<T> {
     SpecOptions {
       name: None,
       retries_: None,
+      retry_policy_: None,
+      repeat_: None,
+      bench_: None,
       slow_: None,
+      tags: vec![],
       hook: None
     }
   }
@@ -34,43 +52,91 @@ impl<T> SpecOptions<T> {
     self.retries_ = Some(retries);
     self
   }
-  pub fn slow(&mut self, threshold: u128) -> &mut Self {
-    self.slow_ = Some(threshold);
+  pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+    self.retry_policy_ = Some(policy);
+    self
+  }
+  pub fn repeat(&mut self, times: u32) -> &mut Self {
+    self.repeat_ = Some(Repeat::times(self.repeat_, times));
+    self
+  }
+  pub fn until_failure(&mut self) -> &mut Self {
+    self.repeat_ = Some(Repeat::until_failure(self.repeat_));
+    self
+  }
+  pub fn bench(&mut self, iterations: u32) -> &mut Self {
+    self.bench_ = Some(Bench::iterations(self.bench_, iterations));
+    self
+  }
+  pub fn warm_up(&mut self, runs: u32) -> &mut Self {
+    self.bench_ = Some(Bench::warm_up(self.bench_, runs));
+    self
+  }
+  pub fn slow<S: Into<SlowThreshold>>(&mut self, threshold: S) -> &mut Self {
+    self.slow_ = Some(threshold.into());
+    self
+  }
+  pub fn tag<S: Into<String> + Display>(&mut self, tag: S) -> &mut Self {
+    self.tags.push(tag.to_string());
     self
   }
 }
 
 pub struct SpecContext<T> {
   pub state: Rc<RefCell<State<T>>>,
+  pub fixtures: Rc<RefCell<FixtureStore>>,
   pub retries_: Option<u32>,
-  pub slow_: Option<u128>,
+  pub retry_policy_: Option<RetryPolicy>,
+  pub repeat_: Option<Repeat>,
+  pub bench_: Option<Bench>,
+  pub slow_: Option<SlowThreshold>,
+  pub tags: Vec<String>,
   pub speed_result: Speed,
   pub attempts: u32,
+  pub seed: u64,
+  pub cases: u32,
 }
 impl<T> SpecContext<T> {
   pub fn new(state: Rc<RefCell<State<T>>>) -> SpecContext<T> {
     SpecContext {
       state,
+      fixtures: Rc::new(RefCell::new(FixtureStore::new())),
       retries_: None,
+      retry_policy_: None,
+      repeat_: None,
+      bench_: None,
       slow_: None,
+      tags: vec![],
       speed_result: Speed::Fast,
-      attempts: 0
+      attempts: 0,
+      seed: 0,
+      cases: DEFAULT_CASES
     }
   }
   pub fn retries(&mut self, count: u32) -> &mut Self {
     self.retries_ = Some(count);
     self
   }
-  pub fn slow(&mut self, count: u128) -> &mut Self {
-    self.slow_ = Some(count);
+  pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+    self.retry_policy_ = Some(policy);
+    self
+  }
+  pub fn slow<S: Into<SlowThreshold>>(&mut self, threshold: S) -> &mut Self {
+    self.slow_ = Some(threshold.into());
     self
   }
   pub fn get_retries(&self) -> Option<&u32> {
     self.retries_.as_ref()
   }
-  pub fn get_slow(&self) -> Option<&u128> {
+  pub fn get_slow(&self) -> Option<&SlowThreshold> {
     self.slow_.as_ref()
   }
+  pub fn fixture<F: 'static>(&self) -> Result<Rc<RefCell<F>>, String> {
+    match self.fixtures.borrow().get::<F>() {
+      Some(fixture) => Ok(fixture),
+      None => Err(format!("Expected fixture {} to be available", std::any::type_name::<F>()))
+    }
+  }
 
 }
 
@@ -78,14 +144,20 @@ pub struct Spec<T> {
   pub name: String,
   pub order: Option<u32>,
   pub only: bool,
-  pub hook: Box<dyn Fn(&mut SpecContext<T>) -> Result<(), String> + 'static>,
+  pub hook: SpecHook<T>,
   pub result: Option<Result<(), String>>,
-  pub duration: u128,
+  pub duration: Duration,
+  pub history: Vec<Attempt>,
+  pub repeat: Option<RepeatStats>,
+  pub bench: Option<BenchStats>,
+  pub baseline: Option<BaselineComparison>,
+  // The data of a spec created by each().
+  pub case: Option<Value>,
   pub context:  SpecContext<T>,
   pub skip: bool
 }
 impl<T> Spec<T> {
-  pub fn new(name: String, state: Rc<RefCell<State<T>>>, hook: Box<dyn Fn(&mut SpecContext<T>) -> Result<(), String>>) -> Spec<T> {
+  pub fn new(name: String, state: Rc<RefCell<State<T>>>, hook: SpecHook<T>) -> Spec<T> {
     let context = SpecContext::new(state);
     Spec {
       name,
@@ -93,9 +165,18 @@ impl<T> Spec<T> {
       only: false,
       hook,
       result: None,
-      duration: 0,
+      duration: Duration::ZERO,
+      history: vec![],
+      repeat: None,
+      bench: None,
+      baseline: None,
+      case: None,
       context,
       skip: false,
     }
   }
+  // A spec is flaky when it passed, but only after failing at least once.
+  pub fn flaky(&self) -> bool {
+    matches!(self.result, Some(Ok(_))) && self.history.iter().any(|attempt| attempt.error.is_some())
+  }
 }
@@ -0,0 +1,85 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+trait StateValue: Any {
+  fn clone_box(&self) -> Box<dyn StateValue>;
+  fn as_any(&self) -> &dyn Any;
+  fn as_any_mut(&mut self) -> &mut dyn Any;
+  fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+impl<U: Any + Clone> StateValue for U {
+  fn clone_box(&self) -> Box<dyn StateValue> {
+    Box::new(self.clone())
+  }
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+  fn as_any_mut(&mut self) -> &mut dyn Any {
+    self
+  }
+  fn into_any(self: Box<Self>) -> Box<dyn Any> {
+    self
+  }
+}
+
+// The state shared by hooks and specs. It dereferences to the keyed
+// HashMap used since the first release and also holds at most one value
+// per type, so suites can keep unrelated values without a common `T`.
+pub struct State<T> {
+  map: HashMap<&'static str, T>,
+  values: HashMap<TypeId, Box<dyn StateValue>>
+}
+impl<T> Default for State<T> {
+  fn default() -> Self {
+    State::new()
+  }
+}
+impl<T: Clone> Clone for State<T> {
+  fn clone(&self) -> Self {
+    State {
+      map: self.map.clone(),
+      values: self.values.iter().map(|(key, value)| (*key, (**value).clone_box())).collect()
+    }
+  }
+}
+impl<T> Deref for State<T> {
+  type Target = HashMap<&'static str, T>;
+  fn deref(&self) -> &Self::Target {
+    &self.map
+  }
+}
+impl<T> DerefMut for State<T> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.map
+  }
+}
+impl<T> State<T> {
+  pub fn new() -> State<T> {
+    State {
+      map: HashMap::new(),
+      values: HashMap::new()
+    }
+  }
+  pub fn insert_value<U: Any + Clone>(&mut self, value: U) -> Option<U> {
+    self.values.insert(TypeId::of::<U>(), Box::new(value))
+      .and_then(|previous| previous.into_any().downcast::<U>().ok())
+      .map(|previous| *previous)
+  }
+  pub fn value<U: Any>(&self) -> Option<&U> {
+    self.values.get(&TypeId::of::<U>())
+      .and_then(|value| (**value).as_any().downcast_ref::<U>())
+  }
+  pub fn value_mut<U: Any>(&mut self) -> Option<&mut U> {
+    self.values.get_mut(&TypeId::of::<U>())
+      .and_then(|value| (**value).as_any_mut().downcast_mut::<U>())
+  }
+  pub fn remove_value<U: Any>(&mut self) -> Option<U> {
+    self.values.remove(&TypeId::of::<U>())
+      .and_then(|value| value.into_any().downcast::<U>().ok())
+      .map(|value| *value)
+  }
+  pub fn has_value<U: Any>(&self) -> bool {
+    self.values.contains_key(&TypeId::of::<U>())
+  }
+}
@@ -1,12 +1,33 @@
 use chrono::offset::Utc;
-use chrono::DateTime;
-use std::{fmt::Display};
-use std::time::{Instant, SystemTime};
+use chrono::{DateTime, SecondsFormat};
+use std::env;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::LabResult;
-use crate::suite_context::SuiteContext;
+use crate::config;
+use crate::baseline::{Baseline, BaselineComparison, DEFAULT_REGRESSION_MIN_DELTA, DEFAULT_REGRESSION_THRESHOLD};
+use crate::fixture::{Fixture, FixtureScope, set_up_fixtures, tear_down_fixtures};
+use crate::harness::{test_name, HarnessArgs, FAILURE_EXIT_CODE};
+use crate::hook_context::{HookContext, HookSpec};
+use crate::prop;
+use crate::repeat::{Repeat, RepeatStats};
+use crate::retry::{Attempt, RetryPolicy};
+use crate::snapshot::{self, SnapshotSummary};
+use crate::spec::{Spec, SpecContext};
+use crate::state::State;
+use crate::suite_context::{Hook, SuiteContext};
 use crate::reporter::{
+  Banner,
+  ColorMode,
   Reporter,
+  report_test_list,
+  report_to_file,
   report_to_stdout
 };
 
@@ -17,47 +38,155 @@ pub enum Speed {
   Slow
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Duration {
-  Nano(u128),
-  Micro(u128),
-  Mil(u128),
-  Sec(u128)
+// The unit durations are shown in. Durations are always measured and
+// stored as a std::time::Duration and only converted when reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationType {
+  Auto,
+  Nano,
+  Micro,
+  Mil,
+  Sec
 }
-impl Duration {
-  pub fn to_string(&self) -> String {
+impl DurationType {
+  // The unit of the numbers in the json reports, which use
+  // milliseconds when the display unit is picked automatically.
+  pub fn unit(self) -> &'static str {
     match self {
-      Duration::Nano(duration) => format!("({}ns)", duration),
-      Duration::Micro(duration) => format!("({}μs)", duration),
-      Duration::Mil(duration) => format!("({}ms)", duration),
-      Duration::Sec(duration) => format!("({}sec)", duration)
+      DurationType::Nano => "ns",
+      DurationType::Micro => "us",
+      DurationType::Auto | DurationType::Mil => "ms",
+      DurationType::Sec => "s"
     }
   }
+  pub fn value(self, duration: Duration) -> f64 {
+    match self {
+      DurationType::Nano => duration.as_nanos() as f64,
+      DurationType::Micro => duration.as_nanos() as f64 / 1_000.0,
+      DurationType::Auto | DurationType::Mil => duration.as_nanos() as f64 / 1_000_000.0,
+      DurationType::Sec => duration.as_secs_f64()
+    }
+  }
+  // Formats a duration in this unit, e.g. "1.23ms". Auto picks the
+  // largest unit the duration reaches. Values under one keep three
+  // significant digits, so 2ms in seconds is "0.002sec" rather than "0sec".
+  pub fn format(self, duration: Duration) -> String {
+    let duration_type = match self {
+      DurationType::Auto if duration < Duration::from_micros(1) => DurationType::Nano,
+      DurationType::Auto if duration < Duration::from_millis(1) => DurationType::Micro,
+      DurationType::Auto if duration < Duration::from_secs(1) => DurationType::Mil,
+      DurationType::Auto => DurationType::Sec,
+      duration_type => duration_type
+    };
+    let label = match duration_type {
+      DurationType::Nano => "ns",
+      DurationType::Micro => "μs",
+      DurationType::Auto | DurationType::Mil => "ms",
+      DurationType::Sec => "sec"
+    };
+    let value = duration_type.value(duration);
+    let precision = if value > 0.0 && value < 1.0 { 2 + value.log10().floor().abs() as usize } else { 2 };
+    let value = format!("{:.*}", precision, value);
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", value, label)
+  }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum DurationType {
-  Nano,
-  Micro,
-  Mil,
-  Sec
+// How long a spec may take before it is reported as slow. A bare number
+// is read in the suite's duration unit, or in nanoseconds when the unit
+// is picked automatically, as they were when nanoseconds were the
+// default unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlowThreshold {
+  Units(u128),
+  Exact(Duration)
+}
+impl SlowThreshold {
+  pub fn to_duration(self, duration_type: DurationType) -> Duration {
+    match self {
+      SlowThreshold::Exact(duration) => duration,
+      SlowThreshold::Units(count) => {
+        let count = count.min(u64::MAX as u128) as u64;
+        match duration_type {
+          DurationType::Auto | DurationType::Nano => Duration::from_nanos(count),
+          DurationType::Micro => Duration::from_micros(count),
+          DurationType::Mil => Duration::from_millis(count),
+          DurationType::Sec => Duration::from_secs(count)
+        }
+      }
+    }
+  }
+}
+impl From<Duration> for SlowThreshold {
+  fn from(duration: Duration) -> Self {
+    SlowThreshold::Exact(duration)
+  }
+}
+impl From<u128> for SlowThreshold {
+  fn from(count: u128) -> Self {
+    SlowThreshold::Units(count)
+  }
+}
+impl From<u64> for SlowThreshold {
+  fn from(count: u64) -> Self {
+    SlowThreshold::Units(count as u128)
+  }
+}
+impl From<u32> for SlowThreshold {
+  fn from(count: u32) -> Self {
+    SlowThreshold::Units(count as u128)
+  }
+}
+impl From<i32> for SlowThreshold {
+  fn from(count: i32) -> Self {
+    SlowThreshold::Units(count.max(0) as u128)
+  }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
 pub struct NullState;
 
+pub type SuiteCallback<T> = Box<dyn Fn(&mut SuiteContext<T>)>;
+pub type StateSnapshot<T> = fn(&State<T>) -> State<T>;
+
 pub struct Suite<T> {
   pub name: String,
   pub only: bool,
-  pub cb: Box<dyn Fn(&mut SuiteContext<T>)>,
+  pub cb: SuiteCallback<T>,
   pub context: SuiteContext<T>,
   pub duration_type: DurationType,
-  pub suite_duration: u128,
-  pub total_duration: u128,
+  pub suite_duration: Duration,
+  pub total_duration: Duration,
   pub depth: u32,
+  pub path: Vec<String>,
   pub reporter: Reporter,
   pub start_time: String,
   pub end_time: String,
   pub ignore_errors: bool,
+  pub color: ColorMode,
+  pub banner: Banner,
+  pub isolate_state: Option<StateSnapshot<T>>,
+  pub repeat: Option<Repeat>,
+  pub slowest: Option<usize>,
+  pub baseline: Option<PathBuf>,
+  pub regression_threshold: f64,
+  pub regression_min_delta: Duration,
+  pub baseline_comparison: Option<BaselineComparison>,
+  pub snapshot_dir: Option<PathBuf>,
+  pub snapshots: SnapshotSummary,
+  pub seed: Option<u64>,
+  pub grep: Option<String>,
+  pub bail: bool,
+  // Whether a panicking spec fails on its own instead of ending the run,
+  // as libtest does. Set in harness mode.
+  pub catch_panics: bool,
+  pub output: Option<PathBuf>,
+  pub timeout: Option<Duration>,
+  // Specs left out by grep() or the harness's filters.
+  pub filtered_out: u32,
+  prepared: bool,
+  pub before_run_hooks: Vec<Hook<T>>,
+  pub after_run_hooks: Vec<Hook<T>>,
 }
 impl<T> Suite<T> {
   pub fn new<N, H>(name: N, cb: H) -> Suite<T> where
@@ -65,39 +194,170 @@ impl<T> Suite<T> {
   H: Fn(&mut SuiteContext<T>) + 'static
   {
     let context = SuiteContext::new();
-    Suite {
+    let mut suite = Suite {
       name: name.to_string(),
       only: false,
       cb: Box::new(cb),
       context,
-      duration_type: DurationType::Nano,
+      duration_type: DurationType::Auto,
       depth: 0,
-      suite_duration: 0,
-      total_duration: 0,
+      path: vec![],
+      suite_duration: Duration::ZERO,
+      total_duration: Duration::ZERO,
       reporter: Reporter::Spec,
       start_time: String::new(),
       end_time: String::new(),
-      ignore_errors: false
+      ignore_errors: false,
+      color: ColorMode::Auto,
+      banner: Banner::Default,
+      isolate_state: None,
+      repeat: None,
+      slowest: None,
+      baseline: None,
+      regression_threshold: DEFAULT_REGRESSION_THRESHOLD,
+      regression_min_delta: DEFAULT_REGRESSION_MIN_DELTA,
+      baseline_comparison: None,
+      snapshot_dir: None,
+      snapshots: SnapshotSummary::default(),
+      seed: None,
+      grep: None,
+      bail: false,
+      catch_panics: false,
+      output: None,
+      timeout: None,
+      filtered_out: 0,
+      prepared: false,
+      before_run_hooks: vec![],
+      after_run_hooks: vec![]
+    };
+    if let Ok(config) = config::get() {
+      config.apply_to_suite(&mut suite);
     }
+    suite
   }
   pub fn run(&mut self) -> LabResult {
-    Suite::run_callbacks(self);
-    Suite::apply_depth_to_suites(self);
-    Suite::index_specs(self, &mut 0);
-    Suite::ignore_non_onlys(self);
-    Suite::apply_hooks(self);
-    Suite::apply_state(self);
-    Suite::apply_duration_type(self);
-    Suite::run_specs_and_suites(self);
+    if let Err(msg) = config::get() {
+      return Err(msg.clone());
+    }
+    Suite::prepare(self);
+    if let Some(pattern) = self.grep.take() {
+      let mut filtered_out = 0;
+      Suite::filter_specs(self, &mut |path, _spec| path.join(" ").contains(pattern.as_str()), &mut filtered_out);
+      self.filtered_out += filtered_out;
+    }
+    let mut before_run_hooks = vec![];
+    let mut after_run_hooks = vec![];
+    Suite::collect_run_hooks(self, &mut before_run_hooks, &mut after_run_hooks);
+    let mut run_fixtures = vec![];
+    Suite::collect_run_fixtures(self, &mut run_fixtures);
+    let active_run_fixtures = set_up_fixtures(&run_fixtures, FixtureScope::Run, &self.context.fixture_store);
+    snapshot::reset();
+    // Run-level teardown has to happen even if a hook or spec panics.
+    let outcome = catch_unwind(AssertUnwindSafe(|| {
+      for hook in &before_run_hooks {
+        (hook)(&mut HookContext::new(&mut self.context.state.borrow_mut(), None));
+      }
+      Suite::run_specs_and_suites(self);
+    }));
+    // Every after_run hook runs even if one of them panics, and the
+    // first panic is the one resumed.
+    let mut panic = outcome.err();
+    for hook in &after_run_hooks {
+      let outcome = catch_unwind(AssertUnwindSafe(|| {
+        (hook)(&mut HookContext::new(&mut self.context.state.borrow_mut(), None));
+      }));
+      if let Err(hook_panic) = outcome {
+        panic.get_or_insert(hook_panic);
+      }
+    }
+    tear_down_fixtures(active_run_fixtures, &self.context.fixture_store);
+    if let Some(panic) = panic {
+      resume_unwind(panic);
+    }
+    self.snapshots = snapshot::take_summary();
+    // A filtered run can't tell the snapshots of the specs it left out
+    // from obsolete ones.
+    if let (Some(dir), 0) = (self.snapshot_dir.clone(), self.filtered_out) {
+      let mut obsolete = vec![];
+      Suite::find_obsolete_snapshots(self, &dir, &self.snapshots, &mut obsolete);
+      self.snapshots.obsolete = obsolete;
+    }
     Suite::sum_result_counts(self);
     Suite::sum_test_durations(self);
     Suite::apply_slow_settings(self);
     Suite::calculate_speed(self);
-    report_to_stdout(&self);
-    if self.context.fail == false || self.ignore_errors == true {
+    let baseline_result = Suite::apply_baseline(self);
+    match self.output.clone() {
+      Some(path) => report_to_file(self, &path)?,
+      None => report_to_stdout(self)
+    }
+    baseline_result?;
+    if !self.context.fail || self.ignore_errors {
+      Ok(())
+    } else {
+      Err("Expected number of failed tests to equal 0".to_string())
+    }
+  }
+  // Runs the suite as the main function of a test target with
+  // `harness = false`. Every spec becomes a test that cargo test, libtest's
+  // flags and IDE test explorers can list, filter and run one by one.
+  pub fn harness(mut self) {
+    let code = match HarnessArgs::parse(env::args().skip(1)) {
+      Ok(args) => match self.run_harness(&args) {
+        Ok(()) => 0,
+        Err(_) => FAILURE_EXIT_CODE
+      },
+      Err(msg) => {
+        eprintln!("error: {}", msg);
+        FAILURE_EXIT_CODE
+      }
+    };
+    process::exit(code);
+  }
+  // Command line options win over the builder settings.
+  pub fn run_harness(&mut self, args: &HarnessArgs) -> LabResult {
+    if let Err(msg) = config::get() {
+      return Err(msg.clone());
+    }
+    if args.color != ColorMode::Auto {
+      self.color = args.color;
+    }
+    self.reporter = args.reporter.unwrap_or(Reporter::Libtest(args.format));
+    if let Some(unit) = args.unit {
+      self.duration_type = unit;
+    }
+    if let Some(seed) = args.seed {
+      self.seed = Some(seed);
+    }
+    if args.grep.is_some() {
+      self.grep = args.grep.clone();
+    }
+    if args.bail {
+      self.bail = true;
+    }
+    if args.output.is_some() {
+      self.output = args.output.clone();
+    }
+    self.catch_panics = true;
+    Suite::prepare(self);
+    // Skipped specs are libtest's ignored tests.
+    let mut filtered_out = 0;
+    Suite::filter_specs(self, &mut |path, spec| {
+      let keep = args.matches(&test_name(path)) && (!args.ignored || spec.skip);
+      if keep && (args.ignored || args.include_ignored) {
+        spec.skip = false;
+      }
+      keep
+    }, &mut filtered_out);
+    self.filtered_out = filtered_out;
+    if args.ignored || args.include_ignored {
+      Suite::unskip_suites(self);
+    }
+    if args.list {
+      report_test_list(self);
       Ok(())
     } else {
-      Err(format!("Expected number of failed tests to equal 0"))
+      self.run()
     }
   }
   pub fn spec(mut self) -> Self {
@@ -132,6 +392,26 @@ impl<T> Suite<T> {
     self.reporter = Reporter::Json(true);
     self
   }
+  pub fn json_tree(mut self) -> Self {
+    self.reporter = Reporter::JsonTree(false);
+    self
+  }
+  pub fn json_tree_pretty(mut self) -> Self {
+    self.reporter = Reporter::JsonTree(true);
+    self
+  }
+  pub fn markdown(mut self) -> Self {
+    self.reporter = Reporter::Markdown;
+    self
+  }
+  pub fn html(mut self) -> Self {
+    self.reporter = Reporter::Html;
+    self
+  }
+  pub fn auto(mut self) -> Self {
+    self.duration_type = DurationType::Auto;
+    self
+  }
   pub fn nano(mut self) -> Self {
     self.duration_type = DurationType::Nano;
     self
@@ -152,61 +432,210 @@ impl<T> Suite<T> {
     self.ignore_errors = true;
     self
   }
+  pub fn repeat(mut self, times: u32) -> Self {
+    self.repeat = Some(Repeat::times(self.repeat, times));
+    self
+  }
+  pub fn until_failure(mut self) -> Self {
+    self.repeat = Some(Repeat::until_failure(self.repeat));
+    self
+  }
+  // Where to_match_snapshot() keeps its files, "snapshots" in the crate
+  // root unless set.
+  pub fn snapshot_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+    self.snapshot_dir = Some(dir.into());
+    self
+  }
+  // The seed prop() specs draw their inputs from, taken from LAB_SEED or
+  // the clock unless set.
+  pub fn seed(mut self, seed: u64) -> Self {
+    self.seed = Some(seed);
+    self
+  }
+  // Only runs the specs whose full name, the names of its suites and its
+  // own joined with spaces, contains the pattern.
+  pub fn grep<S: Into<String>>(mut self, pattern: S) -> Self {
+    self.grep = Some(pattern.into());
+    self
+  }
+  // Stops running specs after the first failure. The specs that didn't
+  // run are reported as skipped.
+  pub fn bail(mut self) -> Self {
+    self.bail = true;
+    self
+  }
+  // Writes the report to a file instead of stdout.
+  pub fn output<P: Into<PathBuf>>(mut self, path: P) -> Self {
+    self.output = Some(path.into());
+    self
+  }
+  // Fails specs that pass but take longer than the timeout. Specs are
+  // not interrupted, the check happens once they return. Benchmarks are
+  // not timed out.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+  pub fn slowest(mut self, count: usize) -> Self {
+    self.slowest = Some(count);
+    self
+  }
+  // Compares spec durations with the ones saved in a json file, which is
+  // written on the first run and rewritten when LAB_UPDATE_BASELINE is set,
+  // but never by a run that grep() or the harness's filters narrowed down.
+  pub fn baseline<P: Into<PathBuf>>(mut self, path: P) -> Self {
+    self.baseline = Some(path.into());
+    self
+  }
+  // How many percent slower than its baseline a spec may run before it is
+  // reported as a regression.
+  pub fn regression_threshold(mut self, percent: f64) -> Self {
+    self.regression_threshold = percent;
+    self
+  }
+  // How much slower than its baseline, in absolute terms, a spec has to
+  // run as well before it is reported as a regression. 1ms by default.
+  pub fn regression_min_delta(mut self, delta: Duration) -> Self {
+    self.regression_min_delta = delta;
+    self
+  }
+  pub fn before_run<H: Fn(&mut HookContext<T>) + 'static>(mut self, hook: H) -> Self {
+    self.before_run_hooks.push(Rc::new(hook));
+    self
+  }
+  pub fn after_run<H: Fn(&mut HookContext<T>) + 'static>(mut self, hook: H) -> Self {
+    self.after_run_hooks.push(Rc::new(hook));
+    self
+  }
+  pub fn color(mut self, mode: ColorMode) -> Self {
+    self.color = mode;
+    self
+  }
+  pub fn banner<S, E>(mut self, start: S, end: E) -> Self
+  where
+    S: Into<String> + Display,
+    E: Into<String> + Display
+  {
+    self.banner = Banner::Custom(start.to_string(), end.to_string());
+    self
+  }
+  pub fn no_banner(mut self) -> Self {
+    self.banner = Banner::Off;
+    self
+  }
   pub fn state(self, state: T) -> Self {
     self.context.state.borrow_mut().insert("/", state);
     self
   }
   fn run_specs_and_suites(suite: &mut Suite<T>) {
+    let snapshot_dir = suite.snapshot_dir.clone().unwrap_or_else(snapshot::default_dir);
     let system_time = SystemTime::now();
     let datetime: DateTime<Utc> = system_time.into();
-    suite.start_time = datetime.to_string();
-    if let Some(boxed_hook) = &suite.context.before_all_hook {
-      let hook = boxed_hook.as_ref();
-      (hook)(&mut suite.context.state.borrow_mut())
+    suite.start_time = datetime.to_rfc3339_opts(SecondsFormat::Micros, true);
+    let suite_fixtures = set_up_fixtures(&suite.context.fixtures, FixtureScope::Suite, &suite.context.fixture_store);
+    for hook in &suite.context.before_all_hooks {
+      (hook)(&mut HookContext::new(&mut suite.context.state.borrow_mut(), None));
+      suite.context.hook_counts.before_all += 1;
     }
     for spec in &mut suite.context.specs {
-      if !spec.skip {
-        let retries: u32 = {
-          if let Some(suite_retries) = suite.context.retries_ {
-            if let Some(spec_retries) = spec.context.retries_ {
-              spec_retries
-            } else {
-              suite_retries
+      let bailed = suite.bail && suite.context.fail;
+      if !spec.skip && !bailed {
+        let bench = spec.context.bench_;
+        let repeat = match bench {
+          Some(_) => None,
+          None => spec.context.repeat_.or(suite.repeat)
+        };
+        // Repeating is meant to surface intermittent failures and benchmarks
+        // measure every run, so retries are not layered on top of either.
+        let policy = match (repeat, bench) {
+          (None, None) => Suite::retry_policy_for(&suite.context.retry_policy_, suite.context.retries_, &spec.context),
+          _ => RetryPolicy::new(0)
+        };
+        let attempts = 1 + policy.retries;
+        let iterations = match repeat {
+          Some(repeat) => repeat.times.max(1),
+          None => 1
+        };
+        spec.context.fixtures = suite.context.fixture_store.clone();
+        spec.context.seed = suite.seed.unwrap_or_default();
+        spec.context.cases = suite.context.cases_.unwrap_or(prop::DEFAULT_CASES);
+        let mut spec_path = suite.path.clone();
+        spec_path.push(spec.name.to_string());
+        for _iteration in 1..=iterations {
+          for attempt in 1..=attempts {
+            let spec_fixtures = set_up_fixtures(&suite.context.fixtures, FixtureScope::Spec, &suite.context.fixture_store);
+            let mut hook_spec = HookSpec {
+              name: spec.name.to_string(),
+              path: spec_path.clone(),
+              full_name: spec_path.join(" "),
+              tags: spec.context.tags.clone(),
+              attempt: spec.context.attempts + 1,
+              result: None,
+              duration: None
+            };
+            for hook in &suite.context.before_each_hooks {
+              (hook)(&mut HookContext::new(&mut suite.context.state.borrow_mut(), Some(hook_spec.clone())));
+              suite.context.hook_counts.before_each += 1;
             }
-          } else {
-            if let Some(spec_retries) = spec.context.retries_ {
-              spec_retries
-            } else {
-              0
+            spec.context.attempts += 1;
+            snapshot::begin_spec(snapshot::suite_dir(&snapshot_dir, &suite.path), &spec.name);
+            let catch_panics = suite.catch_panics;
+            let (result, duration) = match bench {
+              Some(bench) => {
+                let (result, stats) = bench.run(|| call_spec_hook(spec, catch_panics));
+                let duration = stats.mean;
+                spec.bench = Some(stats);
+                (result, duration)
+              },
+              None => {
+                let start_time = Instant::now();
+                let result = call_spec_hook(spec, catch_panics);
+                let duration = start_time.elapsed();
+                match (result, suite.timeout) {
+                  (Ok(()), Some(timeout)) if duration > timeout => (Err(format!(
+                    "Timeout of {} exceeded, the spec took {}",
+                    suite.duration_type.format(timeout),
+                    suite.duration_type.format(duration)
+                  )), duration),
+                  (result, _) => (result, duration)
+                }
+              }
+            };
+            snapshot::end_spec();
+            spec.history.push(Attempt {
+              error: result.clone().err(),
+              duration
+            });
+            spec.result = Some(result);
+            spec.duration = duration;
+            hook_spec.result = spec.result.clone();
+            hook_spec.duration = Some(spec.duration);
+            for hook in &suite.context.after_each_hooks {
+              (hook)(&mut HookContext::new(&mut suite.context.state.borrow_mut(), Some(hook_spec.clone())));
+              suite.context.hook_counts.after_each += 1;
+            }
+            tear_down_fixtures(spec_fixtures, &suite.context.fixture_store);
+            match &spec.result {
+              Some(Err(msg)) if attempt < attempts && policy.should_retry(msg) => {
+                thread::sleep(policy.delay(attempt));
+              },
+              _ => break
             }
           }
-        };
-        let attempts = 1 + retries;
-        for _i in 1..=attempts {
-          if let Some(boxed_hook) = &suite.context.before_each_hook {
-            let hook = boxed_hook.as_ref();
-            (hook)(&mut suite.context.state.borrow_mut())
-          }
-          spec.context.attempts += 1;
-          let start_time = Instant::now();
-          let result = (spec.hook.as_ref())(&mut spec.context);
-          let duration = start_time.elapsed();
-          let duration_int = match suite.duration_type {
-            DurationType::Nano => duration.as_nanos(),
-            DurationType::Micro => duration.as_micros(),
-            DurationType::Mil => duration.as_millis(),
-            DurationType::Sec => duration.as_secs() as u128
-          };
-          spec.result = Some(result);
-          spec.duration = duration_int;
-          if let Some(boxed_hook) = &suite.context.after_each_hook {
-            let hook = boxed_hook.as_ref();
-            (hook)(&mut suite.context.state.borrow_mut())
-          }
-          if spec.result.as_ref().unwrap().is_ok() {
+          if let (Some(Repeat { until_failure: true, .. }), Some(Err(_))) = (repeat, &spec.result) {
             break;
           }
         }
+        if repeat.is_some() {
+          let stats = RepeatStats::from_attempts(&spec.history);
+          spec.duration = stats.mean_duration;
+          if let Some(iteration) = stats.first_failure {
+            let error = spec.history[iteration as usize - 1].error.clone().unwrap_or_default();
+            spec.result = Some(Err(format!("failed {} of {} iterations, first on iteration {}: {}",
+              stats.failed, stats.iterations, iteration, error)));
+          }
+          spec.repeat = Some(stats);
+        }
         if let Some(result) = &spec.result {
           if result.is_err() {
             suite.context.fail = true;
@@ -214,21 +643,89 @@ impl<T> Suite<T> {
         }
       }
     }
+    let isolate_state = suite.isolate_state;
     for child_suite in suite.context.suites.iter_mut() {
-      if !child_suite.context.skip_ {
+      let bailed = suite.bail && suite.context.fail;
+      if !child_suite.context.skip_ && !bailed {
+        child_suite.context.fixture_store = suite.context.fixture_store.clone();
+        let snapshot = match isolate_state {
+          Some(copy) => Some((copy)(&suite.context.state.borrow())),
+          None => None
+        };
         Suite::run_specs_and_suites(child_suite);
-        if child_suite.context.fail == true {
+        if let Some(snapshot) = snapshot {
+          *suite.context.state.borrow_mut() = snapshot;
+        }
+        if child_suite.context.fail {
           suite.context.fail = true;
         }
       }
     }
-    if let Some(boxed_hook) = &suite.context.after_all_hook {
-      let hook = boxed_hook.as_ref();
-      (hook)(&mut suite.context.state.borrow_mut())
+    for hook in &suite.context.after_all_hooks {
+      (hook)(&mut HookContext::new(&mut suite.context.state.borrow_mut(), None));
+      suite.context.hook_counts.after_all += 1;
     }
+    tear_down_fixtures(suite_fixtures, &suite.context.fixture_store);
     let system_time = SystemTime::now();
     let datetime: DateTime<Utc> = system_time.into();
-    suite.end_time = datetime.to_string();
+    suite.end_time = datetime.to_rfc3339_opts(SecondsFormat::Micros, true);
+  }
+  // A retry policy or retry count set on the spec wins over the suite's.
+  fn retry_policy_for(suite_policy: &Option<RetryPolicy>, suite_retries: Option<u32>, spec_context: &SpecContext<T>) -> RetryPolicy {
+    if let Some(policy) = &spec_context.retry_policy_ {
+      policy.clone()
+    } else if let Some(retries) = spec_context.retries_ {
+      RetryPolicy::new(retries)
+    } else if let Some(policy) = suite_policy {
+      policy.clone()
+    } else {
+      RetryPolicy::new(suite_retries.unwrap_or_default())
+    }
+  }
+  // Builds the suite tree and applies the settings that are passed down
+  // to child suites, once, before specs are filtered or run.
+  fn prepare(suite: &mut Suite<T>) {
+    if suite.prepared {
+      return;
+    }
+    suite.prepared = true;
+    Suite::run_callbacks(suite);
+    if let Ok(config) = config::get() {
+      config.apply_to_context(suite, true);
+    }
+    Suite::apply_depth_to_suites(suite);
+    Suite::apply_paths(suite, vec![]);
+    Suite::index_specs(suite, &mut 0);
+    Suite::ignore_non_onlys(suite);
+    Suite::apply_hooks(suite);
+    Suite::apply_state(suite);
+    Suite::apply_duration_type(suite);
+    Suite::apply_repeat(suite);
+    Suite::apply_snapshot_dir(suite);
+    Suite::apply_seed(suite);
+    Suite::apply_bail(suite);
+    Suite::apply_catch_panics(suite);
+    Suite::apply_timeout(suite);
+    Suite::apply_fixtures(suite);
+  }
+  // Drops the specs `keep` rejects, given their path, and suites left
+  // without any, so their hooks don't run either.
+  fn filter_specs<F: FnMut(&[String], &mut Spec<T>) -> bool>(suite: &mut Suite<T>, keep: &mut F, filtered_out: &mut u32) {
+    let count = suite.context.specs.len();
+    let path = suite.path.clone();
+    suite.context.specs.retain_mut(|spec| {
+      let mut spec_path = path.clone();
+      spec_path.push(spec.name.to_string());
+      keep(&spec_path, spec)
+    });
+    *filtered_out += (count - suite.context.specs.len()) as u32;
+    for child_suite in suite.context.suites.iter_mut() {
+      Suite::filter_specs(child_suite, keep, filtered_out);
+    }
+    suite.context.suites.retain(|child_suite| Suite::has_specs(child_suite));
+  }
+  fn has_specs(suite: &Suite<T>) -> bool {
+    !suite.context.specs.is_empty() || suite.context.suites.iter().any(Suite::has_specs)
   }
   fn run_callbacks(suite: &mut Suite<T>) {
     (suite.cb)(&mut suite.context);
@@ -250,30 +747,83 @@ impl<T> Suite<T> {
       child_suite.depth += suite.depth;
     }
   }
+  fn apply_paths(suite: &mut Suite<T>, mut path: Vec<String>) {
+    path.push(suite.name.to_string());
+    suite.path = path;
+    for child_suite in suite.context.suites.iter_mut() {
+      Suite::apply_paths(child_suite, suite.path.clone());
+    }
+  }
+  // Parent before_each hooks run ahead of the child's own hooks and
+  // parent after_each hooks run after them, like mocha and jest.
   fn apply_hooks(suite: &mut Suite<T>) {
     for child_suite in suite.context.suites.iter_mut() {
-      if let Some(hook) = &suite.context.before_each_hook {
-        if let None = child_suite.context.before_each_hook {
-          child_suite.context.before_each_hook = Some(hook.clone());
-        }
-      }
-      if let Some(hook) = &suite.context.after_each_hook {
-        if let None = child_suite.context.after_each_hook {
-          child_suite.context.after_each_hook = Some(hook.clone());
-        }
-      }
+      let mut before_each_hooks = suite.context.before_each_hooks.clone();
+      before_each_hooks.append(&mut child_suite.context.before_each_hooks);
+      child_suite.context.before_each_hooks = before_each_hooks;
+      child_suite.context.after_each_hooks.extend(suite.context.after_each_hooks.iter().cloned());
+      Suite::apply_hooks(child_suite);
     }
   }
   fn apply_state(suite: &mut Suite<T>) {
     for child_suite in suite.context.suites.iter_mut() {
       child_suite.context.state = suite.context.state.clone();
+      // An imported suite keeps its own isolate_state().
+      child_suite.isolate_state = child_suite.isolate_state.or(suite.isolate_state);
+      Suite::apply_state(child_suite);
     }
     // for spec in suite.context.specs.iter_mut() {
     //   spec.context.state = suite.context.state.clone();
     // }
   }
+  fn apply_fixtures(suite: &mut Suite<T>) {
+    for child_suite in suite.context.suites.iter_mut() {
+      let mut inherited: Vec<Fixture> = suite.context.fixtures.iter()
+        .filter(|fixture| fixture.scope == FixtureScope::Spec)
+        .cloned()
+        .collect();
+      inherited.append(&mut child_suite.context.fixtures);
+      child_suite.context.fixtures = inherited;
+      Suite::apply_fixtures(child_suite);
+    }
+  }
+  // Imported suites may bring their own run hooks. Setup runs from the root
+  // down, teardown from the innermost suite back up to the root.
+  fn collect_run_hooks(suite: &Suite<T>, before_run_hooks: &mut Vec<Hook<T>>, after_run_hooks: &mut Vec<Hook<T>>) {
+    before_run_hooks.extend(suite.before_run_hooks.iter().cloned());
+    for child_suite in &suite.context.suites {
+      if !child_suite.context.skip_ {
+        Suite::collect_run_hooks(child_suite, before_run_hooks, after_run_hooks);
+      }
+    }
+    after_run_hooks.extend(suite.after_run_hooks.iter().cloned());
+  }
+  fn collect_run_fixtures(suite: &Suite<T>, fixtures: &mut Vec<Fixture>) {
+    for fixture in &suite.context.fixtures {
+      if fixture.scope == FixtureScope::Run {
+        fixtures.push(fixture.clone());
+      }
+    }
+    for child_suite in &suite.context.suites {
+      if !child_suite.context.skip_ {
+        Suite::collect_run_fixtures(child_suite, fixtures);
+      }
+    }
+  }
+  // Clears the skip flag of the suites holding specs that are no longer
+  // skipped, which the harness's --ignored and --include-ignored do.
+  fn unskip_suites(suite: &mut Suite<T>) -> bool {
+    let mut runs = suite.context.specs.iter().any(|spec| !spec.skip);
+    for child_suite in suite.context.suites.iter_mut() {
+      runs |= Suite::unskip_suites(child_suite);
+    }
+    if runs {
+      suite.context.skip_ = false;
+    }
+    runs
+  }
   fn ignore_non_onlys(suite: &mut Suite<T>) {
-    if suite.context.skip_ == true {
+    if suite.context.skip_  {
       for spec in &mut suite.context.specs {
         spec.skip = true;
       }
@@ -317,12 +867,12 @@ impl<T> Suite<T> {
   fn apply_retries(suite: &mut Suite<T>) {
     if let Some(retries) = suite.context.retries_ {
       for spec in &mut suite.context.specs {
-        if let None = spec.context.retries_ {
+        if spec.context.retries_.is_none() {
           spec.context.retries_ = Some(retries);
         }
       }
       for child_suite in &mut suite.context.suites {
-        if let None = child_suite.context.retries_ {
+        if child_suite.context.retries_.is_none() {
           child_suite.context.retries_ = Some(retries);
         }
       }
@@ -331,20 +881,94 @@ impl<T> Suite<T> {
       }
     }
   }
+  fn apply_snapshot_dir(suite: &mut Suite<T>) {
+    let dir = suite.snapshot_dir.get_or_insert_with(snapshot::default_dir).clone();
+    for child_suite in suite.context.suites.iter_mut() {
+      child_suite.snapshot_dir = Some(dir.clone());
+      Suite::apply_snapshot_dir(child_suite);
+    }
+  }
+  fn apply_bail(suite: &mut Suite<T>) {
+    let bail = suite.bail;
+    for child_suite in suite.context.suites.iter_mut() {
+      child_suite.bail = bail;
+      Suite::apply_bail(child_suite);
+    }
+  }
+  fn apply_catch_panics(suite: &mut Suite<T>) {
+    let catch_panics = suite.catch_panics;
+    for child_suite in suite.context.suites.iter_mut() {
+      child_suite.catch_panics = catch_panics;
+      Suite::apply_catch_panics(child_suite);
+    }
+  }
+  fn apply_timeout(suite: &mut Suite<T>) {
+    let timeout = suite.timeout;
+    for child_suite in suite.context.suites.iter_mut() {
+      child_suite.timeout = timeout;
+      Suite::apply_timeout(child_suite);
+    }
+  }
+  fn apply_seed(suite: &mut Suite<T>) {
+    let seed = *suite.seed.get_or_insert_with(prop::default_seed);
+    let cases = suite.context.cases_;
+    for child_suite in suite.context.suites.iter_mut() {
+      child_suite.seed = Some(seed);
+      if child_suite.context.cases_.is_none() {
+        child_suite.context.cases_ = cases;
+      }
+      Suite::apply_seed(child_suite);
+    }
+  }
+  // Snapshot files of specs that passed without checking them, of specs
+  // that no longer exist and of suites that no longer exist.
+  fn find_obsolete_snapshots(suite: &Suite<T>, root: &Path, summary: &SnapshotSummary, obsolete: &mut Vec<PathBuf>) {
+    let dir = snapshot::suite_dir(root, &suite.path);
+    for file in snapshot::snap_files(&dir) {
+      let stem = file.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+      let spec_slug = stem.split('@').next().unwrap_or_default();
+      let is_obsolete = match suite.context.specs.iter().find(|spec| snapshot::slug(&spec.name) == spec_slug) {
+        Some(spec) => matches!(spec.result, Some(Ok(_))) && !summary.touched(&file),
+        None => true
+      };
+      if is_obsolete {
+        obsolete.push(file);
+      }
+    }
+    for sub_dir in snapshot::sub_dirs(&dir) {
+      let known = suite.context.suites.iter()
+        .any(|child_suite| sub_dir.file_name().is_some_and(|name| *name == *snapshot::slug(&child_suite.name)));
+      if !known {
+        obsolete.extend(snapshot::all_snap_files(&sub_dir));
+      }
+    }
+    for child_suite in &suite.context.suites {
+      Suite::find_obsolete_snapshots(child_suite, root, summary, obsolete);
+    }
+  }
+  fn apply_repeat(suite: &mut Suite<T>) {
+    for child_suite in suite.context.suites.iter_mut() {
+      if child_suite.repeat.is_none() {
+        child_suite.repeat = suite.repeat;
+      }
+      Suite::apply_repeat(child_suite);
+    }
+  }
   fn apply_duration_type(suite: &mut Suite<T>) {
     for child_suite in &mut suite.context.suites {
       child_suite.duration_type = suite.duration_type;
+      Suite::apply_duration_type(child_suite);
     }
   }
   fn apply_slow_settings(suite: &mut Suite<T>) {
     if let Some(slow_setting) = suite.context.slow_ {
       for spec in &mut suite.context.specs {
-        if let None = spec.context.slow_ {
+        if spec.context.slow_.is_none() {
           spec.context.slow_ = Some(slow_setting);
         }
       }
       for child_suite in &mut suite.context.suites {
-        if let None = child_suite.context.slow_ {
+        if child_suite.context.slow_.is_none() {
           child_suite.context.slow_ = Some(slow_setting);
         }
       }
@@ -382,10 +1006,55 @@ impl<T> Suite<T> {
       suite.total_duration += child_suite.total_duration;
     }
   }
+  fn apply_baseline(suite: &mut Suite<T>) -> LabResult {
+    let path = match &suite.baseline {
+      Some(path) => path.clone(),
+      None => return Ok(())
+    };
+    // A filtered run only has the durations of some specs, so it never
+    // writes the baseline, but still compares against an existing one.
+    if Baseline::should_write(&path) && suite.filtered_out == 0 {
+      let mut baseline = Baseline::new(suite.total_duration);
+      Suite::collect_baseline(suite, &mut baseline);
+      baseline.save(&path)
+    } else if !path.exists() {
+      Ok(())
+    } else {
+      let baseline = Baseline::load(&path)?;
+      let threshold = (suite.regression_threshold, suite.regression_min_delta);
+      let total_duration = Duration::from_nanos(baseline.total_duration.min(u64::MAX as u128) as u64);
+      suite.baseline_comparison = Some(BaselineComparison::new(total_duration, suite.total_duration, threshold.0, threshold.1));
+      Suite::compare_to_baseline(suite, &baseline, threshold);
+      Ok(())
+    }
+  }
+  fn collect_baseline(suite: &Suite<T>, baseline: &mut Baseline) {
+    for spec in &suite.context.specs {
+      if let Some(Ok(_)) = spec.result {
+        let key = format!("{} › {}", suite.path.join(" › "), spec.name);
+        baseline.specs.insert(key, spec.duration.as_nanos());
+      }
+    }
+    for child_suite in &suite.context.suites {
+      Suite::collect_baseline(child_suite, baseline);
+    }
+  }
+  fn compare_to_baseline(suite: &mut Suite<T>, baseline: &Baseline, threshold: (f64, Duration)) {
+    for spec in &mut suite.context.specs {
+      let key = format!("{} › {}", suite.path.join(" › "), spec.name);
+      if let (Some(Ok(_)), Some(duration)) = (&spec.result, baseline.get(&key)) {
+        spec.baseline = Some(BaselineComparison::new(duration, spec.duration, threshold.0, threshold.1));
+      }
+    }
+    for child_suite in &mut suite.context.suites {
+      Suite::compare_to_baseline(child_suite, baseline, threshold);
+    }
+  }
   fn calculate_speed(suite: &mut Suite<T>) {
     for spec in &mut suite.context.specs {
-      if let Some(slow_time) = spec.context.slow_{
-        let fast_time = ((slow_time as f64) / 2.0) as u128;
+      if let Some(slow_setting) = spec.context.slow_ {
+        let slow_time = slow_setting.to_duration(suite.duration_type);
+        let fast_time = slow_time / 2;
         if spec.duration > slow_time {
           spec.context.speed_result = Speed::Slow;
         } else if spec.duration <= fast_time {
@@ -403,25 +1072,48 @@ impl<T> Suite<T> {
   }
 }
 
+impl<T: Clone> Suite<T> {
+  // Every child suite works on a copy of its parent's state that is
+  // discarded once the child finishes, so siblings never see each other's writes.
+  // The copy is a full clone of the parent's state, taken once before each
+  // child suite runs (specs inside the child share it), at every level of
+  // nesting. Keep the state small or cheap to clone, e.g. behind an Rc.
+  pub fn isolate_state(mut self) -> Self {
+    self.isolate_state = Some(State::clone);
+    self
+  }
+}
+
+// Runs the spec once. When panics are caught, a panic fails the spec with
+// its message and the run goes on with the next spec.
+fn call_spec_hook<T>(spec: &mut Spec<T>, catch_panics: bool) -> LabResult {
+  if !catch_panics {
+    return (spec.hook.as_ref())(&mut spec.context);
+  }
+  let hook = spec.hook.as_ref();
+  let context = &mut spec.context;
+  catch_unwind(AssertUnwindSafe(|| (hook)(context))).unwrap_or_else(|panic| {
+    let msg = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+      (Some(msg), _) => msg.to_string(),
+      (None, Some(msg)) => msg.clone(),
+      (None, None) => "Box<dyn Any>".to_string()
+    };
+    Err(format!("panicked: {}", msg))
+  })
+}
+
 pub fn describe<T, S, H>(name: S, cb: H) -> Suite<T>
   where
     S: Into<String> + Display,
     H: Fn(&mut SuiteContext<T>) + 'static
 {
-  let context = SuiteContext::new();
-  // (cb)(&mut context);
-  Suite {
-    name: name.to_string(),
-    only: false,
-    cb: Box::new(cb),
-    context,
-    duration_type: DurationType::Nano,
-    depth: 0,
-    suite_duration: 0,
-    total_duration: 0,
-    reporter: Reporter::Spec,
-    start_time: String::new(),
-    end_time: String::new(),
-    ignore_errors: false
-  }
+  Suite::new(name, cb)
+}
+
+pub fn describe_typed<S, H>(name: S, cb: H) -> Suite<NullState>
+  where
+    S: Into<String> + Display,
+    H: Fn(&mut SuiteContext<NullState>) + 'static
+{
+  describe(name, cb)
 }
@@ -1,59 +1,100 @@
-use std::collections::HashMap;
+use serde::Serialize;
+use serde_json::{to_value, Value};
 use std::{fmt::Display, rc::Rc, cell::RefCell};
+use crate::bench::Bench;
+use crate::each::case_name;
+use crate::hook_context::HookContext;
+use crate::prop::{self, Gen};
+use crate::registry::registered_suites;
+use crate::fixture::{Fixture, FixtureScope, FixtureStore};
+use crate::retry::RetryPolicy;
 use crate::spec::{Spec, SpecContext, SpecOptions};
-use crate::suite::{Suite};
+use crate::state::State;
+use crate::suite::{SlowThreshold, Suite};
 
-pub type State<T> = HashMap<&'static str, T>;
+pub type Hook<T> = Rc<dyn Fn(&mut HookContext<T>) + 'static>;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HookCounts {
+  pub before_all: u32,
+  pub before_each: u32,
+  pub after_each: u32,
+  pub after_all: u32
+}
 
 pub struct SuiteContext<T> {
   pub state: Rc<RefCell<State<T>>>,
-  pub after_all_hook: Option<Rc<dyn Fn(&mut State<T>) + 'static>>,
-  pub after_each_hook: Option<Rc<dyn Fn(&mut State<T>) + 'static>>,
-  pub before_all_hook: Option<Rc<dyn Fn(&mut State<T>) + 'static>>,
-  pub before_each_hook: Option<Rc<dyn Fn(&mut State<T>) + 'static>>,
+  pub after_all_hooks: Vec<Hook<T>>,
+  pub after_each_hooks: Vec<Hook<T>>,
+  pub before_all_hooks: Vec<Hook<T>>,
+  pub before_each_hooks: Vec<Hook<T>>,
+  pub fixtures: Vec<Fixture>,
+  pub fixture_store: Rc<RefCell<FixtureStore>>,
   pub specs: Vec<Spec<T>>,
   pub suites: Vec<Suite<T>>,
   pub retries_: Option<u32>,
+  pub retry_policy_: Option<RetryPolicy>,
   pub skip_: bool,
-  pub slow_: Option<u128>,
+  pub slow_: Option<SlowThreshold>,
+  pub cases_: Option<u32>,
   pub passed: u32,
   pub failed: u32,
   pub ignored: u32,
+  pub hook_counts: HookCounts,
   pub fail: bool
 }
+impl<T> Default for SuiteContext<T> {
+  fn default() -> Self {
+    SuiteContext::new()
+  }
+}
 impl<T> SuiteContext<T> {
   pub fn new() -> SuiteContext<T> {
     SuiteContext {
-      state: Rc::new(RefCell::new(HashMap::new())),
-      after_all_hook: None,
-      after_each_hook: None,
-      before_all_hook: None,
-      before_each_hook: None,
+      state: Rc::new(RefCell::new(State::new())),
+      after_all_hooks: vec![],
+      after_each_hooks: vec![],
+      before_all_hooks: vec![],
+      before_each_hooks: vec![],
+      fixtures: vec![],
+      fixture_store: Rc::new(RefCell::new(FixtureStore::new())),
       specs: vec![],
       suites: vec![],
       retries_: None,
+      retry_policy_: None,
       skip_: false,
       slow_: None,
+      cases_: None,
       passed: 0,
       failed: 0,
       ignored: 0,
+      hook_counts: HookCounts::default(),
       fail: false
     }
   }
-  pub fn before_all<H: Fn(&mut State<T>) + 'static>(&mut self, hook: H) -> &mut Self {
-    self.before_all_hook = Some(Rc::new(hook));
+  pub fn before_all<H: Fn(&mut HookContext<T>) + 'static>(&mut self, hook: H) -> &mut Self {
+    self.before_all_hooks.push(Rc::new(hook));
     self
   }
-  pub fn before_each<H: Fn(&mut State<T>) + 'static>(&mut self, hook: H) -> &mut Self {
-    self.before_each_hook = Some(Rc::new(hook));
+  pub fn before_each<H: Fn(&mut HookContext<T>) + 'static>(&mut self, hook: H) -> &mut Self {
+    self.before_each_hooks.push(Rc::new(hook));
     self
   }
-  pub fn after_all<H: Fn(&mut State<T>) + 'static>(&mut self, hook: H) -> &mut Self {
-    self.after_all_hook = Some(Rc::new(hook));
+  pub fn after_all<H: Fn(&mut HookContext<T>) + 'static>(&mut self, hook: H) -> &mut Self {
+    self.after_all_hooks.push(Rc::new(hook));
     self
   }
-  pub fn after_each<H: Fn(&mut State<T>) + 'static>(&mut self, hook: H) -> &mut Self {
-    self.after_each_hook = Some(Rc::new(hook));
+  pub fn after_each<H: Fn(&mut HookContext<T>) + 'static>(&mut self, hook: H) -> &mut Self {
+    self.after_each_hooks.push(Rc::new(hook));
+    self
+  }
+  pub fn fixture<F, S, D>(&mut self, scope: FixtureScope, setup: S, teardown: D) -> &mut Self
+  where
+    F: 'static,
+    S: Fn() -> F + 'static,
+    D: Fn(&mut F) + 'static
+  {
+    self.fixtures.push(Fixture::new(scope, setup, teardown));
     self
   }
   pub fn it<S, H>(&mut self, name: S, hook: H) -> &mut Self
@@ -84,6 +125,59 @@ impl<T> SuiteContext<T> {
     self.specs.push(spec);
     self
   }
+  // A spec that is run many times after a few untimed warm-up runs and
+  // reported with its mean, median, standard deviation and throughput.
+  // Use spec() with bench() and warm_up() to change the number of runs.
+  pub fn bench<S, H>(&mut self, name: S, hook: H) -> &mut Self
+  where
+    S: Into<String> + Display,
+    H: Fn(&mut SpecContext<T>) -> Result<(), String> + 'static
+  {
+    let mut spec = Spec::new(name.to_string(), self.state.clone(), Box::new(hook));
+    spec.context.bench_ = Some(Bench::default());
+    self.specs.push(spec);
+    self
+  }
+  // One spec per case, all sharing the suite's hooks. The name is a
+  // template filled in from the case, e.g. "returns {1} for {0}" for a
+  // tuple, and the case is included in the json reports.
+  pub fn each<C, I, S, H>(&mut self, cases: I, name: S, hook: H) -> &mut Self
+  where
+    I: IntoIterator<Item = C>,
+    C: Serialize + Clone + 'static,
+    S: Into<String> + Display,
+    H: Fn(C, &mut SpecContext<T>) -> Result<(), String> + 'static
+  {
+    let template = name.to_string();
+    let hook = Rc::new(hook);
+    for case in cases {
+      let data = to_value(&case).unwrap_or(Value::Null);
+      let hook = hook.clone();
+      let mut spec = Spec::new(case_name(&template, &data), self.state.clone(), Box::new(move |spec: &mut SpecContext<T>| {
+        (hook)(case.clone(), spec)
+      }));
+      spec.case = Some(data);
+      self.specs.push(spec);
+    }
+    self
+  }
+  // A spec that checks a property against inputs drawn from `gen`, a
+  // generator or a tuple of generators. A failing input is shrunk and the
+  // smallest one that still fails is reported with the seed of the run.
+  pub fn prop<S, G, H>(&mut self, name: S, gen: G, hook: H) -> &mut Self
+  where
+    S: Into<String> + Display,
+    G: Gen + 'static,
+    H: Fn(G::Value, &mut SpecContext<T>) -> Result<(), String> + 'static
+  {
+    let name = name.to_string();
+    let prop_name = name.clone();
+    self.specs.push(Spec::new(name, self.state.clone(), Box::new(move |spec: &mut SpecContext<T>| {
+      let (seed, cases) = (spec.seed, spec.cases);
+      prop::check(&gen, seed, &prop_name, cases, |input| (hook)(input, spec))
+    })));
+    self
+  }
   pub fn spec<H>(&mut self, cb: H) -> &mut Self
   where
   H: Fn(&mut SpecOptions<T>)
@@ -95,7 +189,11 @@ impl<T> SuiteContext<T> {
         Some(hook) => {
           let mut spec = Spec::new(name.to_string(), self.state.clone(), hook);
           spec.context.retries_ = options.retries_;
+          spec.context.retry_policy_ = options.retry_policy_;
+          spec.context.repeat_ = options.repeat_;
+          spec.context.bench_ = options.bench_;
           spec.context.slow_ = options.slow_;
+          spec.context.tags = options.tags;
           self.specs.push(spec);
         },
         None => { /* no hook skips the test */}
@@ -164,8 +262,27 @@ impl<T> SuiteContext<T> {
     self.retries_ = Some(count);
     self
   }
-  pub fn slow(&mut self, count: u128) -> &mut Self {
-    self.slow_ = Some(count);
+  pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+    self.retry_policy_ = Some(policy);
+    self
+  }
+  pub fn slow<S: Into<SlowThreshold>>(&mut self, threshold: S) -> &mut Self {
+    self.slow_ = Some(threshold.into());
+    self
+  }
+  // The number of inputs every prop() spec in this suite is checked against.
+  pub fn cases(&mut self, count: u32) -> &mut Self {
+    self.cases_ = Some(count);
+    self
+  }
+}
+impl<T: 'static> SuiteContext<T> {
+  // Imports every suite registered with #[register] or register_suite()
+  // that has the same state type as this one.
+  pub fn describe_registered(&mut self) -> &mut Self {
+    for suite in registered_suites::<T>() {
+      self.describe_import(suite);
+    }
     self
   }
 }
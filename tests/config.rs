@@ -0,0 +1,57 @@
+// The config is read once per process, so the suites here run against a
+// laboratory.toml of their own, pointed at with LAB_CONFIG before the
+// first suite is created. Keep this to a single test.
+
+use std::time::Duration;
+use laboratory::{describe_typed, expect, DurationType, LabResult, NullState, Reporter, SlowThreshold, CONFIG_FILE, CONFIG_VAR};
+
+#[test]
+fn apply_the_config_to_every_suite() -> LabResult {
+
+  let dir = std::env::temp_dir().join(format!("laboratory-config-suite-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+  std::fs::write(dir.join(CONFIG_FILE), "reporter = \"min\"\nduration_unit = \"ms\"\nslow = 20\nretries = 1\noutput = \"lab.txt\"\n")
+    .map_err(|err| err.to_string())?;
+  std::env::set_var(CONFIG_VAR, dir.join(CONFIG_FILE));
+  std::env::set_var("LAB_RETRIES", "2");
+
+  let mut suite = describe_typed("config", |ctx| {
+    ctx.it("always fails", |_spec| {
+      Err("broken".to_string())
+    });
+    ctx.describe("child", |ctx| {
+      ctx.it("always fails", |_spec| {
+        Err("broken".to_string())
+      });
+    });
+    ctx.describe("own settings", |ctx| {
+      ctx.retries(0).slow(Duration::from_secs(1));
+      ctx.it("always fails", |_spec| {
+        Err("broken".to_string())
+      });
+    });
+  }).ignore_errors();
+  // Defaults are applied when the suite is created.
+  expect((suite.reporter, suite.duration_type, suite.output.clone())).to_equal((Reporter::Min, DurationType::Mil, Some(dir.join("lab.txt"))))?;
+
+  // Builder calls made afterwards win.
+  suite = suite.dot();
+  let run = suite.run();
+  let report = std::fs::read_to_string(dir.join("lab.txt")).map_err(|err| err.to_string());
+  let _ = std::fs::remove_dir_all(&dir);
+  run?;
+
+  let attempts = |suite: &laboratory::Suite<NullState>| suite.context.specs[0].context.attempts;
+  let slow = |suite: &laboratory::Suite<NullState>| suite.context.slow_;
+  let (child, own) = (&suite.context.suites[0], &suite.context.suites[1]);
+  // LAB_RETRIES wins over the file and is set on every suite that didn't
+  // set its own, while slow is set on the root and passed down from there.
+  expect((attempts(&suite), attempts(child), attempts(own))).to_equal((3, 3, 1))?;
+  expect((slow(&suite), slow(child), slow(own))).to_equal((
+    Some(SlowThreshold::Exact(Duration::from_millis(20))),
+    Some(SlowThreshold::Exact(Duration::from_millis(20))),
+    Some(SlowThreshold::Exact(Duration::from_secs(1)))
+  ))?;
+  expect((suite.reporter, report?.contains("Lab Results"))).to_equal((Reporter::Dot, true))
+
+}
@@ -0,0 +1,26 @@
+// A test target with `harness = false`: every spec is its own test, so
+// `cargo test --test harness -- --list` lists them and filters pick them.
+
+use laboratory::{describe_typed, expect};
+
+fn main() {
+
+  describe_typed("harness", |ctx| {
+
+    ctx.it("runs a spec as a test", |_spec| {
+      expect(1 + 1).to_equal(2)
+    });
+
+    ctx.it_skip("reports skipped specs as ignored", |_spec| {
+      expect(1).to_equal(2)
+    });
+
+    ctx.describe("child suite", |suite| {
+      suite.it("nests test names", |_spec| {
+        expect(true).to_be(true)
+      });
+    });
+
+  }).harness()
+
+}
@@ -1,27 +1,76 @@
-use std::rc::Rc;
-use std::cell::RefCell;
+use std::panic::catch_unwind;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
 
-use laboratory::{ describe, expect, should_panic, LabResult };
+use laboratory::{ describe, describe_typed, expect, BaselineComparison, ColorMode, Config, CONFIG_FILE, CONFIG_VAR, DurationType, FixtureScope, HarnessArgs, HarnessFormat, HookContext, LabResult, NullState, Reporter, RetryPolicy, Suite };
+use serde_json::{json, Value};
+
+// Runs the suite with its report written to a temporary file, named
+// after `name`, and returns the report or the run's error.
+fn run_to_string<T>(name: &str, suite: &mut Suite<T>) -> Result<String, String> {
+  let path = std::env::temp_dir().join(format!("laboratory-{}-{}.out", name, std::process::id()));
+  suite.output = Some(path.clone());
+  let result = suite.run();
+  let report = std::fs::read_to_string(&path).map_err(|err| err.to_string());
+  let _ = std::fs::remove_file(&path);
+  result.and(report)
+}
+
+// Like run_to_string(), for a suite run as a harness with `args`.
+fn run_harness_to_string<T>(name: &str, suite: &mut Suite<T>, args: &[&str]) -> Result<String, String> {
+  let path = std::env::temp_dir().join(format!("laboratory-{}-{}.out", name, std::process::id()));
+  let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+  args.extend(vec!["--output".to_string(), path.display().to_string()]);
+  let result = HarnessArgs::parse(args).and_then(|args| suite.run_harness(&args));
+  let report = std::fs::read_to_string(&path).map_err(|err| err.to_string());
+  let _ = std::fs::remove_file(&path);
+  result.and(report)
+}
+
+fn run_to_json<T>(name: &str, suite: &mut Suite<T>) -> Result<Value, String> {
+  let report = run_to_string(name, suite)?;
+  serde_json::from_str(&report).map_err(|err| err.to_string())
+}
+
+// Checks the field of a json report at `pointer`, e.g. "/stats/tests",
+// naming the field when it doesn't match.
+fn expect_field(report: &Value, pointer: &str, expected: Value) -> LabResult {
+  let actual = report.pointer(pointer).cloned().unwrap_or(Value::Null);
+  expect(actual).to_equal(expected).map_err(|msg| format!("{}: {}", pointer, msg))
+}
+
+fn expect_number(report: &Value, pointer: &str) -> Result<f64, String> {
+  report.pointer(pointer).and_then(Value::as_f64).ok_or_else(|| format!("{}: Expected a number", pointer))
+}
+
+fn expect_contains(report: &str, text: &str, contains: bool) -> LabResult {
+  if report.contains(text) == contains {
+    Ok(())
+  } else {
+    Err(format!("Expected the report {} contain {:?}:\n{}", if contains { "to" } else { "not to" }, text, report))
+  }
+}
 
 #[test]
 fn describe_a_suite() -> LabResult {
-  
+
   describe("my suite", |ctx| {
 
     ctx.before_all(move |state| {
       state.insert("counter", 0);
     });
 
-    ctx.before_each(move |state| {
+    ctx.before_each(move |_state| {
 
     });
 
-    ctx.after_all(move |state| {
+    ctx.after_all(move |_state| {
       // println!("count: {}", state.as_ref().borrow());
     });
 
     ctx.describe("child", |suite| {
-      suite.it("test", |spec| {
+      suite.it("test", |_spec| {
         expect(true).to_be(true)
       });
     });
@@ -33,3 +82,993 @@ fn describe_a_suite() -> LabResult {
   }).rust().nano().run()
 
 }
+
+#[test]
+fn report_a_suite_as_a_json_tree() -> LabResult {
+
+  let report = run_to_json("json-tree", &mut describe("my suite", |ctx| {
+
+    ctx.before_each(move |state| {
+      state.insert("counter", 0);
+    });
+
+    ctx.it("test", |_spec| {
+      expect(true).to_be(true)
+    });
+
+    ctx.describe("child suite", |suite| {
+      suite.it("test with spaces", |_spec| {
+        expect(1).to_equal(1)
+      });
+      suite.it_skip("skipped test", |_spec| {
+        expect(1).to_equal(2)
+      });
+    });
+
+  }).json_tree_pretty().nano())?;
+
+  expect_field(&report, "/title", json!("my suite"))?;
+  expect_field(&report, "/specs/0/path", json!(["my suite", "test"]))?;
+  expect_field(&report, "/stats/tests", json!(1))?;
+  expect_field(&report, "/stats/passing", json!(1))?;
+  expect_field(&report, "/hooks/before_each", json!(1))?;
+  expect_field(&report, "/suites/0/title", json!("child suite"))?;
+  expect_field(&report, "/suites/0/path", json!(["my suite", "child suite"]))?;
+  expect_field(&report, "/suites/1", Value::Null)?;
+  expect_field(&report, "/suites/0/stats/tests", json!(2))?;
+  expect_field(&report, "/suites/0/stats/passing", json!(1))?;
+  expect_field(&report, "/suites/0/stats/pending", json!(1))?;
+  expect_field(&report, "/suites/0/specs/0/path", json!(["my suite", "child suite", "test with spaces"]))?;
+  expect_field(&report, "/suites/0/specs/1/status", json!("pending"))?;
+  expect_field(&report, "/suites/0/hooks/before_each", json!(1))
+
+}
+
+#[test]
+fn report_rfc_3339_timestamps_and_the_duration_unit_in_json() -> LabResult {
+
+  let report = run_to_json("json", &mut describe_typed("timestamps", |ctx| {
+    ctx.it("passes", |_spec| {
+      expect(1).to_equal(1)
+    });
+    ctx.describe("child", |ctx| {
+      ctx.it("sleeps", |_spec| {
+        sleep(Duration::from_millis(2));
+        Ok(())
+      });
+      ctx.describe("grandchild", |ctx| {
+        ctx.it("sleeps too", |_spec| {
+          sleep(Duration::from_millis(1));
+          Ok(())
+        });
+      });
+    });
+  }).json().micro())?;
+
+  for pointer in ["/stats/start", "/stats/end"].iter() {
+    let time = report.pointer(pointer).and_then(Value::as_str).unwrap_or_default();
+    chrono::DateTime::parse_from_rfc3339(time).map_err(|err| format!("{}: {} ({:?})", pointer, err, time))?;
+  }
+  expect_field(&report, "/stats/duration_unit", json!("us"))?;
+  let mut sum = 0.0;
+  for index in 0..3 {
+    expect_field(&report, &format!("/tests/{}/duration_unit", index), json!("us"))?;
+    sum += expect_number(&report, &format!("/tests/{}/duration", index))?;
+  }
+  // The total is converted once, so it matches its specs in the same unit.
+  let total = expect_number(&report, "/stats/duration")?;
+  expect(total >= 3000.0 && (total - sum).abs() < 0.01).to_be(true)
+    .map_err(|_| format!("/stats/duration: Expected {} to be the sum of its specs, {}, in microseconds", total, sum))
+
+}
+
+#[test]
+fn control_colours_in_reports() -> LabResult {
+
+  let suite = |mode: ColorMode| describe_typed("colours", |ctx| {
+    ctx.it("passes", |_spec| {
+      expect(1).to_equal(1)
+    });
+  }).spec().color(mode);
+
+  expect_contains(&run_to_string("color-always", &mut suite(ColorMode::Always))?, "\u{1b}[", true)?;
+  expect_contains(&run_to_string("color-never", &mut suite(ColorMode::Never))?, "\u{1b}[", false)?;
+
+  // NO_COLOR wins over CLICOLOR_FORCE. NO_COLOR is set first and removed
+  // last so other tests never run with colour forced on.
+  std::env::set_var("NO_COLOR", "1");
+  std::env::set_var("CLICOLOR_FORCE", "1");
+  let report = run_to_string("no-color", &mut suite(ColorMode::Auto));
+  std::env::remove_var("CLICOLOR_FORCE");
+  std::env::remove_var("NO_COLOR");
+  expect_contains(&report?, "\u{1b}[", false)
+
+}
+
+#[test]
+fn print_a_custom_banner_or_none() -> LabResult {
+
+  let suite = || describe_typed("banner", |ctx| {
+    ctx.it("passes", |_spec| {
+      expect(1).to_equal(1)
+    });
+  }).spec().color(ColorMode::Never);
+
+  let default = run_to_string("banner-default", &mut suite())?;
+  expect_contains(&default, "### Lab Results Start ###", true)?;
+  expect_contains(&default, "### Lab Results End ###", true)?;
+
+  let custom = run_to_string("banner-custom", &mut suite().banner("== start ==", "== end =="))?;
+  expect_contains(&custom, "== start ==", true)?;
+  expect_contains(&custom, "== end ==", true)?;
+  expect_contains(&custom, "### Lab Results", false)?;
+
+  let off = run_to_string("banner-off", &mut suite().no_banner())?;
+  expect_contains(&off, "###", false)?;
+  expect_contains(&off, "banner", true)
+
+}
+
+#[test]
+fn provide_typed_fixtures_to_nested_suites() -> LabResult {
+
+  describe_typed("my suite", |ctx| {
+
+    ctx.fixture(FixtureScope::Run, || String::from("run"), |_| {});
+
+    ctx.fixture(FixtureScope::Spec, || 0_u32, |_| {});
+
+    ctx.it("increments its own counter", |spec| {
+      let counter = spec.fixture::<u32>()?;
+      *counter.borrow_mut() += 1;
+      let value = *counter.borrow();
+      expect(value).to_equal(1)
+    });
+
+    ctx.describe("child", |suite| {
+      suite.fixture(FixtureScope::Suite, || vec![1, 2, 3], |_| {});
+      suite.it("gets a fresh counter", |spec| {
+        let value = *spec.fixture::<u32>()?.borrow();
+        expect(value).to_equal(0)
+      });
+      suite.it("reads suite and run fixtures", |spec| {
+        let run = spec.fixture::<String>()?.borrow().to_string();
+        let len = spec.fixture::<Vec<i32>>()?.borrow().len();
+        expect((run.as_str(), len)).to_equal(("run", 3))
+      });
+    });
+
+    ctx.it("does not see the child's fixtures", |spec| {
+      expect(spec.fixture::<Vec<i32>>().is_err()).to_be(true)
+    });
+
+  }).run()
+
+}
+
+#[test]
+fn leave_run_fixtures_of_skipped_suites_alone() -> LabResult {
+
+  static SET_UP: AtomicU32 = AtomicU32::new(0);
+  static TORN_DOWN: AtomicU32 = AtomicU32::new(0);
+
+  describe_typed("my suite", |ctx| {
+
+    ctx.it("runs", |_spec| {
+      Ok(())
+    });
+
+    ctx.describe_skip("skipped", |suite| {
+      suite.fixture(FixtureScope::Run, || SET_UP.fetch_add(1, Ordering::SeqCst), |_| {
+        TORN_DOWN.fetch_add(1, Ordering::SeqCst);
+      });
+      suite.it("is skipped", |_spec| {
+        Ok(())
+      });
+    });
+
+  }).run()?;
+
+  expect(SET_UP.load(Ordering::SeqCst)).to_equal(0).map_err(|msg| format!("setup: {}", msg))?;
+  expect(TORN_DOWN.load(Ordering::SeqCst)).to_equal(0).map_err(|msg| format!("teardown: {}", msg))
+
+}
+
+#[test]
+fn isolate_state_between_sibling_suites() -> LabResult {
+
+  describe("parent", |ctx| {
+
+    ctx.before_all(|state| {
+      state.insert("count", 0);
+    });
+
+    ctx.describe("first child", |suite| {
+      suite.before_all(|state| {
+        state.insert("leak", 1);
+        if let Some(count) = state.get_mut("count") {
+          *count += 1;
+        }
+      });
+      suite.it("reads through to the parent", |spec| {
+        let count = spec.state.borrow().get("count").copied();
+        expect(count).to_equal(Some(1))
+      });
+    });
+
+    ctx.describe("second child", |suite| {
+      suite.it("does not see its sibling's writes", |spec| {
+        let state = spec.state.borrow();
+        expect((state.get("leak").copied(), state.get("count").copied())).to_equal((None, Some(0)))
+      });
+    });
+
+    ctx.describe("last child", |suite| {
+      suite.it("sees the parent state as it was", |spec| {
+        let state = spec.state.borrow();
+        expect((state.get("leak").copied(), state.get("count").copied())).to_equal((None, Some(0)))
+      });
+    });
+
+  }).isolate_state().run()
+
+}
+
+#[test]
+fn keep_an_imported_suite_isolated() -> LabResult {
+
+  fn imported() -> Suite<i32> {
+    describe("imported", |ctx| {
+      ctx.describe("writer", |suite| {
+        suite.it("writes", |spec| {
+          spec.state.borrow_mut().insert("leak", 1);
+          Ok(())
+        });
+      });
+      ctx.describe("reader", |suite| {
+        suite.it("does not see the write", |spec| {
+          expect(spec.state.borrow().get("leak").copied()).to_equal(None)
+        });
+      });
+    }).isolate_state()
+  }
+
+  describe("parent", |ctx| {
+    ctx.describe_import(imported());
+  }).run()
+
+}
+
+#[test]
+fn pass_the_running_spec_to_each_hooks() -> LabResult {
+
+  describe_typed("hooks", |ctx| {
+
+    ctx.before_all(|ctx| {
+      ctx.insert_value(Vec::<(String, u32, bool)>::new());
+    });
+
+    ctx.after_each(|ctx| {
+      let record = ctx.spec().map(|spec| (spec.full_name.to_string(), spec.attempt, spec.passed()));
+      if let (Some(record), Some(records)) = (record, ctx.value_mut::<Vec<(String, u32, bool)>>()) {
+        records.push(record);
+      }
+    });
+
+    ctx.spec(|spec| {
+      spec.it("passes on the second attempt", |spec| {
+        expect(spec.attempts).to_equal(2)
+      }).retries(1);
+    });
+
+    ctx.after_all(|ctx| {
+      assert_eq!(ctx.value::<Vec<(String, u32, bool)>>(), Some(&vec![
+        ("hooks passes on the second attempt".to_string(), 1, false),
+        ("hooks passes on the second attempt".to_string(), 2, true)
+      ]));
+    });
+
+  }).run()
+
+}
+
+#[test]
+fn compose_multiple_hooks_across_nested_suites() -> LabResult {
+
+  fn record(ctx: &mut HookContext<Vec<&'static str>>, entry: &'static str) {
+    ctx.entry("log").or_default().push(entry);
+  }
+
+  describe("parent", |ctx| {
+
+    ctx.before_each(|ctx| record(ctx, "parent before 1"));
+    ctx.before_each(|ctx| record(ctx, "parent before 2"));
+    ctx.after_each(|ctx| record(ctx, "parent after 1"));
+    ctx.after_each(|ctx| record(ctx, "parent after 2"));
+
+    ctx.describe("child", |suite| {
+      suite.before_each(|ctx| record(ctx, "child before"));
+      suite.after_each(|ctx| record(ctx, "child after"));
+      suite.it("runs", |_spec| {
+        Ok(())
+      });
+    });
+
+    ctx.after_all(|ctx| {
+      assert_eq!(ctx.get("log"), Some(&vec![
+        "parent before 1",
+        "parent before 2",
+        "child before",
+        "child after",
+        "parent after 1",
+        "parent after 2"
+      ]));
+    });
+
+  }).run()
+
+}
+
+#[test]
+fn run_after_run_hooks_when_a_spec_panics() {
+
+  static STARTED: AtomicU32 = AtomicU32::new(0);
+  static STOPPED: AtomicU32 = AtomicU32::new(0);
+  static TORN_DOWN: AtomicU32 = AtomicU32::new(0);
+
+  fn imported() -> Suite<NullState> {
+    describe_typed("imported", |ctx| {
+      ctx.fixture(FixtureScope::Run, || String::from("server"), |_| {
+        TORN_DOWN.fetch_add(1, Ordering::SeqCst);
+      });
+      ctx.it("panics", |_spec| {
+        panic!("the server went away")
+      });
+    }).before_run(|_| {
+      STARTED.fetch_add(1, Ordering::SeqCst);
+    }).after_run(|_| {
+      panic!("the hook failed too")
+    }).after_run(|_| {
+      STOPPED.fetch_add(1, Ordering::SeqCst);
+    })
+  }
+
+  let result = catch_unwind(|| {
+    describe_typed("server", |ctx| {
+      ctx.describe_import(imported());
+    }).before_run(|_| {
+      STARTED.fetch_add(1, Ordering::SeqCst);
+    }).after_run(|_| {
+      STOPPED.fetch_add(1, Ordering::SeqCst);
+    }).run()
+  });
+
+  // The spec's panic is the one resumed, after every hook and teardown ran.
+  let panic = result.err().and_then(|panic| panic.downcast_ref::<&str>().map(|msg| msg.to_string()));
+  assert_eq!(panic.as_deref(), Some("the server went away"));
+  assert_eq!(STARTED.load(Ordering::SeqCst), 2);
+  assert_eq!(STOPPED.load(Ordering::SeqCst), 2);
+  assert_eq!(TORN_DOWN.load(Ordering::SeqCst), 1);
+
+}
+
+#[test]
+fn stop_retrying_when_the_policy_rejects_the_error() {
+
+  static ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+
+  let result = describe_typed("retry policy", |ctx| {
+
+    ctx.spec(|spec| {
+      spec.it("fails with an error that is not retried", |_spec| {
+        match ATTEMPTS.fetch_add(1, Ordering::SeqCst) {
+          0 => Err("connection timed out".to_string()),
+          _ => Err("invalid response".to_string())
+        }
+      }).retry_policy(RetryPolicy::new(5).fixed(Duration::from_millis(1)).retry_if(|msg| msg.contains("timed out")));
+    });
+
+  }).run();
+
+  assert!(result.is_err());
+  assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 2);
+
+}
+
+#[test]
+fn repeat_a_spec_until_it_fails() {
+
+  static RUNS: AtomicU32 = AtomicU32::new(0);
+
+  let result = describe_typed("repeat", |ctx| {
+
+    ctx.it("fails on the fourth run", |_spec| {
+      match RUNS.fetch_add(1, Ordering::SeqCst) {
+        3 => Err("intermittent".to_string()),
+        _ => Ok(())
+      }
+    });
+
+  }).repeat(10).until_failure().run();
+
+  assert!(result.is_err());
+  assert_eq!(RUNS.load(Ordering::SeqCst), 4);
+
+}
+
+#[test]
+fn keep_sub_second_durations_when_reporting_in_seconds() -> LabResult {
+
+  let mut suite = describe_typed("durations", |ctx| {
+
+    ctx.it("sleeps for a few milliseconds", |_spec| {
+      sleep(Duration::from_millis(5));
+      Ok(())
+    }).slow(Duration::from_millis(1));
+
+  }).sec();
+
+  suite.run()?;
+
+  let spec = &suite.context.specs[0];
+  expect(spec.duration >= Duration::from_millis(5)).to_be(true)?;
+  expect(format!("{:?}", spec.context.speed_result)).to_equal("Slow".to_string())?;
+  expect(DurationType::Sec.format(Duration::from_micros(2057))).to_equal("0.00206sec".to_string())?;
+  expect(DurationType::Sec.format(Duration::from_millis(1500))).to_equal("1.5sec".to_string())?;
+  expect(DurationType::Mil.format(Duration::from_nanos(50))).to_equal("0.00005ms".to_string())?;
+  expect(DurationType::Nano.format(Duration::ZERO)).to_equal("0ns".to_string())
+
+}
+
+#[test]
+fn read_a_bare_slow_threshold_in_nanoseconds_by_default() -> LabResult {
+
+  let mut suite = describe_typed("durations", |ctx| {
+
+    ctx.it("sleeps for two milliseconds", |_spec| {
+      sleep(Duration::from_millis(2));
+      Ok(())
+    }).slow(1000);
+
+  });
+
+  suite.run()?;
+
+  let spec = &suite.context.specs[0];
+  expect((suite.duration_type, format!("{:?}", spec.context.speed_result))).to_equal((DurationType::Auto, "Slow".to_string()))
+
+}
+
+#[test]
+fn report_grandchild_suites_in_the_root_unit() -> LabResult {
+
+  let suite = || describe_typed("durations", |ctx| {
+    ctx.describe("child", |ctx| {
+      ctx.describe("grandchild", |ctx| {
+        ctx.it("sleeps for two milliseconds", |_spec| {
+          sleep(Duration::from_millis(2));
+          Ok(())
+        }).slow(1000);
+      });
+    });
+  });
+
+  let mut micro = suite().json().micro();
+  let report = run_to_json("grandchild-json", &mut micro)?;
+  expect_field(&report, "/tests/0/title", json!("sleeps for two milliseconds"))?;
+  expect_field(&report, "/tests/0/duration_unit", json!("us"))?;
+  let duration = expect_number(&report, "/tests/0/duration")?;
+  expect(duration >= 2000.0).to_be(true).map_err(|_| format!("/tests/0/duration: Expected {} to be in microseconds", duration))?;
+
+  // A bare slow(1000) is read in the root's unit, so 1000μs here.
+  let grandchild = &micro.context.suites[0].context.suites[0];
+  expect(grandchild.duration_type).to_equal(DurationType::Micro)?;
+  expect(format!("{:?}", grandchild.context.specs[0].context.speed_result)).to_equal("Slow".to_string())?;
+
+  let list = run_to_string("grandchild-list", &mut suite().list().sec().color(ColorMode::Never))?;
+  expect_contains(&list, "ms)", false)
+
+}
+
+#[test]
+fn summarise_the_slowest_specs_with_percentiles() -> LabResult {
+
+  let report = run_to_json("slowest", &mut describe_typed("durations", |ctx| {
+    ctx.it("fast", |_spec| {
+      expect(1).to_equal(1)
+    });
+    ctx.it("slow", |_spec| {
+      std::thread::sleep(std::time::Duration::from_millis(20));
+      expect(1).to_equal(1)
+    });
+    ctx.describe("child", |ctx| {
+      ctx.it("medium", |_spec| {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        expect(1).to_equal(1)
+      });
+    });
+  }).json().milis().slowest(2))?;
+
+  expect_field(&report, "/durations/slowest_specs/0/title", json!("slow"))?;
+  expect_field(&report, "/durations/slowest_specs/1/title", json!("medium"))?;
+  expect_field(&report, "/durations/slowest_specs/2", Value::Null)?;
+  expect_field(&report, "/durations/duration_unit", json!("ms"))?;
+  let slowest = expect_number(&report, "/durations/slowest_specs/0/duration")?;
+  let p50 = expect_number(&report, "/durations/percentiles/p50")?;
+  let p99 = expect_number(&report, "/durations/percentiles/p99")?;
+  expect(p50 >= 10.0 && p50 < slowest).to_be(true).map_err(|_| format!("p50: Expected {} to be the medium spec's duration", p50))?;
+  expect(p99).to_equal(slowest).map_err(|msg| format!("p99: {}", msg))
+
+}
+
+#[test]
+fn bench_a_spec_after_warming_up() -> LabResult {
+
+  static RUNS: AtomicU32 = AtomicU32::new(0);
+
+  let mut suite = describe_typed("bench", |ctx| {
+
+    ctx.spec(|spec| {
+      spec.it("counts its runs", |_spec| {
+        RUNS.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+      }).warm_up(5).bench(50);
+    });
+
+  }).json();
+
+  suite.run()?;
+
+  let stats = suite.context.specs[0].bench.clone().ok_or("Expected bench stats")?;
+  expect((RUNS.load(Ordering::SeqCst), stats.iterations)).to_equal((55, 50))?;
+  expect(stats.min <= stats.median && stats.median <= stats.max).to_be(true)
+
+}
+
+#[test]
+fn flag_specs_that_regressed_against_a_baseline() -> LabResult {
+
+  fn suite(delay: u64, baseline: &std::path::Path) -> Suite<NullState> {
+    describe_typed("baseline", move |ctx| {
+      ctx.it("sleeps", move |_spec| {
+        sleep(Duration::from_millis(delay));
+        Ok(())
+      });
+    }).baseline(baseline).regression_threshold(50.0).repeat(2).list()
+  }
+
+  let dir = std::env::temp_dir().join(format!("laboratory-baseline-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+
+  suite(2, &dir.join("baseline.json")).output(dir.join("first.txt")).run()?;
+  let mut slower = suite(20, &dir.join("baseline.json")).output(dir.join("slower.txt"));
+  let result = slower.run();
+  let report = std::fs::read_to_string(dir.join("slower.txt")).map_err(|err| err.to_string());
+  let _ = std::fs::remove_dir_all(&dir);
+  result?;
+
+  let comparison = slower.context.specs[0].baseline.ok_or("Expected a baseline comparison")?;
+  expect((comparison.regressed, comparison.change > 50.0)).to_equal((true, true))?;
+  // The repeat and regression notes are both shown.
+  let report = report?;
+  expect((report.contains("[2 of 2 passed"), report.contains("(regressed +"))).to_equal((true, true))?;
+
+  // Relative changes too small to be more than noise aren't regressions.
+  let noise = BaselineComparison::new(Duration::from_nanos(100), Duration::from_nanos(300), 20.0, Duration::from_millis(1));
+  let slower = BaselineComparison::new(Duration::from_millis(10), Duration::from_millis(20), 20.0, Duration::from_millis(1));
+  expect((noise.change, noise.regressed, slower.regressed)).to_equal((200.0, false, true))
+
+}
+
+#[test]
+fn only_write_a_baseline_from_unfiltered_runs() -> LabResult {
+
+  let suite = |baseline: &std::path::Path| describe_typed("baseline", |ctx| {
+    ctx.it("parses", |_spec| {
+      Ok(())
+    });
+    ctx.it("lexes", |_spec| {
+      Ok(())
+    });
+  }).baseline(baseline).no_banner();
+
+  let dir = std::env::temp_dir().join(format!("laboratory-baseline-filtered-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+  let path = dir.join("baseline.json");
+
+  let filtered = run_to_string("baseline-filtered", &mut suite(&path).grep("parses")).map(|_| path.exists());
+  run_to_string("baseline-full", &mut suite(&path))?;
+  let written = std::fs::read_to_string(&path).map_err(|err| err.to_string());
+  let mut compared = suite(&path).grep("lexes");
+  let result = run_to_string("baseline-compared", &mut compared);
+  let _ = std::fs::remove_dir_all(&dir);
+  result?;
+
+  expect(filtered?).to_equal(false).map_err(|msg| format!("baseline written by a filtered run: {}", msg))?;
+  let written: Value = serde_json::from_str(&written?).map_err(|err| err.to_string())?;
+  expect(written["specs"].as_object().map(|specs| specs.len())).to_equal(Some(2))?;
+  expect(compared.context.specs[0].baseline.is_some()).to_be(true)
+    .map_err(|_| "Expected the filtered run to compare against the baseline".to_string())
+
+}
+
+#[test]
+fn escape_names_and_messages_in_markdown_and_html() -> LabResult {
+
+  let suite = || describe_typed("parse <a|b>", |ctx| {
+    ctx.it("keeps a | b\nin one cell", |_spec| {
+      Ok(())
+    });
+    ctx.it("rejects <script> & \"quotes\"", |_spec| {
+      Err("expected </li><script>alert(1)</script>\n```\nafter the fence".to_string())
+    });
+  }).no_banner().ignore_errors();
+
+  let dir = std::env::temp_dir().join(format!("laboratory-escaping-{}", std::process::id()));
+  let mut markdown = suite().markdown().output(dir.join("report.md"));
+  let mut html = suite().html().output(dir.join("report.html"));
+  markdown.run()?;
+  html.run()?;
+  let markdown = std::fs::read_to_string(dir.join("report.md")).map_err(|err| err.to_string());
+  let html = std::fs::read_to_string(dir.join("report.html")).map_err(|err| err.to_string());
+  let _ = std::fs::remove_dir_all(&dir);
+  let (markdown, html) = (markdown?, html?);
+
+  // The spec cell of every table row, which an unescaped | or line break
+  // would split.
+  let cells: Vec<&str> = markdown.lines()
+    .filter(|line| line.starts_with("| ✅") || line.starts_with("| ❌"))
+    .map(|line| line.split(" | ").nth(1).unwrap_or_default())
+    .collect();
+  expect(cells).to_equal(vec!["keeps a \\| b in one cell", "rejects \\<script\\> \\& \"quotes\""])?;
+  expect((markdown.contains("# Lab Results: parse \\<a\\|b\\>"), markdown.contains("````text"))).to_equal((true, true))?;
+  expect((html.contains("<script>"), html.contains("&lt;/li&gt;&lt;script&gt;"), html.contains("rejects &lt;script&gt; &amp; &quot;quotes&quot;"))).to_equal((false, true, true))
+
+}
+
+#[test]
+fn compare_values_with_stored_snapshots() -> LabResult {
+
+  fn suite(value: Vec<&'static str>, dir: &std::path::Path) -> Suite<NullState> {
+    describe_typed("parser", move |ctx| {
+      let value = value.clone();
+      ctx.it("parses tokens", move |_spec| {
+        expect(value.clone()).to_match_snapshot("tokens")
+      });
+    }).snapshot_dir(dir)
+  }
+
+  let dir = std::env::temp_dir().join(format!("laboratory-snapshots-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(dir.join("parser")).map_err(|err| err.to_string())?;
+  std::fs::write(dir.join("parser").join("removed_spec@tokens.snap"), "[]").map_err(|err| err.to_string())?;
+
+  let mut first = suite(vec!["let", "x"], &dir);
+  let written = first.run();
+  let mut second = suite(vec!["let", "y"], &dir).ignore_errors();
+  let mismatch = second.run();
+  let failure = second.context.specs[0].result.clone();
+  // Specs left out by a filter don't make their snapshots obsolete.
+  let mut filtered = suite(vec!["let", "x"], &dir).grep("lexes");
+  let filtered_run = filtered.run();
+  let _ = std::fs::remove_dir_all(&dir);
+
+  written?;
+  mismatch?;
+  filtered_run?;
+  expect((first.snapshots.written.len(), first.snapshots.obsolete.len(), filtered.snapshots.obsolete.len())).to_equal((1, 1, 0))?;
+  let failure = failure.and_then(|result| result.err()).unwrap_or_default();
+  expect(failure.contains("-     \"x\",\n+     \"y\",")).to_be(true)
+
+}
+
+#[test]
+fn diff_large_snapshots_in_bounded_memory() -> LabResult {
+
+  fn lines(prefix: &str) -> Vec<String> {
+    let mut lines = vec!["first".to_string()];
+    lines.extend((0..2000).map(|i| format!("{}{}", prefix, i)));
+    lines.insert(1000, "middle".to_string());
+    lines.push("last".to_string());
+    lines
+  }
+
+  let suite = |value: Vec<String>, dir: &std::path::Path| describe_typed("large", move |ctx| {
+    let value = value.clone();
+    ctx.it("renders", move |_spec| {
+      expect(value.clone()).to_match_snapshot("lines")
+    });
+  }).snapshot_dir(dir).ignore_errors().no_banner();
+
+  let dir = std::env::temp_dir().join(format!("laboratory-large-snapshots-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  let written = run_to_string("large-written", &mut suite(lines("old "), &dir));
+  let mut changed = suite(lines("new "), &dir);
+  let result = run_to_string("large-changed", &mut changed);
+  let _ = std::fs::remove_dir_all(&dir);
+  written?;
+  result?;
+
+  // 2000 changed lines on each side would need a table of four million
+  // cells, so they are shown as one block removed and one block added,
+  // even the line they share in the middle.
+  let failure = changed.context.specs[0].result.clone().and_then(|result| result.err()).unwrap_or_default();
+  expect_contains(&failure, "      \"first\",\n-     \"old 0\",", true)?;
+  expect_contains(&failure, "-     \"old 1999\",\n+     \"new 0\",", true)?;
+  expect_contains(&failure, "+     \"new 1999\",\n      \"last\",", true)?;
+  expect_contains(&failure, "-     \"middle\",", true)?;
+  expect_contains(&failure, "+     \"middle\",", true)
+
+}
+
+#[test]
+fn shrink_a_failing_property_to_its_smallest_input() -> LabResult {
+
+  let mut suite = describe_typed("numbers", |ctx| {
+    ctx.prop("stay below 50", 0..1000u32, |x, _spec| {
+      expect(x < 50).to_be(true)
+    });
+    ctx.prop("add up in any order", (0..100i32, 0..100i32), |(a, b), _spec| {
+      expect(a + b).to_equal(b + a)
+    });
+  }).seed(42).ignore_errors();
+
+  suite.run()?;
+
+  let failure = suite.context.specs[0].result.clone().and_then(|result| result.err()).unwrap_or_default();
+  expect(failure.contains("minimal input: 50\n") && failure.contains("seed 42")).to_be(true)?;
+  expect(suite.context.specs[1].result.clone()).to_equal(Some(Ok(())))
+
+}
+
+#[test]
+fn expand_a_table_of_cases_into_specs() -> LabResult {
+
+  let mut suite = describe_typed("double()", |ctx| {
+    ctx.before_each(|_ctx| {});
+    ctx.each(vec![(1, 2), (2, 4), (3, 7)], "returns {1} for {0}", |(input, expected), _spec| {
+      expect(input * 2).to_equal(expected)
+    });
+  }).ignore_errors();
+
+  suite.run()?;
+
+  let names: Vec<&str> = suite.context.specs.iter().map(|spec| spec.name.as_str()).collect();
+  expect(names).to_equal(vec!["returns 2 for 1", "returns 4 for 2", "returns 7 for 3"])?;
+  expect((suite.context.passed, suite.context.failed, suite.context.hook_counts.before_each)).to_equal((2, 1, 3))?;
+  expect(suite.context.specs[2].case.clone()).to_equal(Some(serde_json::json!([3, 7])))
+
+}
+
+#[test]
+fn filter_specs_like_libtest() -> LabResult {
+
+  let mut suite = describe_typed("harness", |ctx| {
+    ctx.it("one", |_spec| {
+      expect(1).to_equal(1)
+    });
+    ctx.it_skip("two", |_spec| {
+      expect(2).to_equal(3)
+    });
+    ctx.describe("child", |suite| {
+      suite.it("three", |_spec| {
+        expect(3).to_equal(3)
+      });
+    });
+    ctx.describe_skip("skipped", |suite| {
+      suite.it("four", |_spec| {
+        expect(4).to_equal(4)
+      });
+    });
+  }).ignore_errors();
+
+  let args = HarnessArgs::parse(vec!["harness::", "--include-ignored", "--skip", "child", "--format=json"])?;
+  suite.run_harness(&args)?;
+
+  let results: Vec<_> = suite.context.specs.iter().map(|spec| spec.result.clone()).collect();
+  expect((results, suite.context.suites.len(), suite.filtered_out)).to_equal((vec![Some(Ok(())), Some(Err("Expected 2 to equal 3".to_string()))], 1, 1))?;
+  expect(suite.context.suites[0].context.specs[0].result.clone()).to_equal(Some(Ok(())))?;
+
+  // Only the ignored specs, including the ones in a skipped suite.
+  let mut suite = describe_typed("harness", |ctx| {
+    ctx.it("one", |_spec| {
+      expect(1).to_equal(1)
+    });
+    ctx.describe_skip("skipped", |suite| {
+      suite.it("two", |_spec| {
+        expect(2).to_equal(2)
+      });
+    });
+  });
+  suite.run_harness(&HarnessArgs::parse(vec!["--ignored", "--format=json"])?)?;
+
+  expect((suite.context.specs.len(), suite.context.suites[0].context.specs[0].result.clone())).to_equal((0, Some(Ok(()))))
+
+}
+
+#[test]
+fn parse_command_line_options() -> LabResult {
+
+  let reporter = |args: Vec<&str>| HarnessArgs::parse(args).map(|args| args.reporter);
+
+  expect((
+    reporter(vec!["--reporter", "libtest", "--format", "json"])?,
+    reporter(vec!["--format=terse", "--reporter=libtest"])?,
+    reporter(vec!["--reporter", "tap"])?,
+    reporter(vec!["--reporter", "xml"]).is_err()
+  )).to_equal((
+    Some(Reporter::Libtest(HarnessFormat::Json)),
+    Some(Reporter::Libtest(HarnessFormat::Terse)),
+    Some(Reporter::Tap),
+    true
+  ))?;
+  let args = HarnessArgs::parse(vec!["--unit", "us", "--seed=7", "--bail", "-o", "lab.json", "name"])?;
+  expect((args.unit, args.seed, args.bail, args.output, args.filters)).to_equal((
+    Some(DurationType::Micro),
+    Some(7),
+    true,
+    Some(std::path::PathBuf::from("lab.json")),
+    vec!["name".to_string()]
+  ))
+
+}
+
+#[test]
+fn fail_a_panicking_spec_in_harness_mode() -> LabResult {
+
+  let mut suite = describe_typed("harness", |ctx| {
+    ctx.it("passes", |_spec| {
+      expect(1).to_equal(1)
+    });
+    ctx.it("indexes out of bounds", |_spec| {
+      let values: Vec<u32> = vec![];
+      expect(values[1]).to_equal(0)
+    });
+    ctx.it("still runs", |_spec| {
+      expect(2).to_equal(2)
+    });
+  }).ignore_errors();
+
+  let report = run_harness_to_string("harness-panic", &mut suite, &["--format", "json", "-Z", "unstable-options"])?;
+  let events: Vec<Value> = report.lines().map(serde_json::from_str).collect::<Result<_, _>>().map_err(|err| err.to_string())?;
+
+  let event = |name: &str| events.iter()
+    .find(|event| event["type"] == "test" && event["name"] == name && event["event"] != "started")
+    .cloned()
+    .unwrap_or(Value::Null);
+  expect_field(&event("harness::passes"), "/event", json!("ok"))?;
+  expect_field(&event("harness::indexes_out_of_bounds"), "/event", json!("failed"))?;
+  let stdout = event("harness::indexes_out_of_bounds")["stdout"].as_str().unwrap_or_default().to_string();
+  expect_contains(&stdout, "panicked: index out of bounds", true)?;
+  expect_field(&event("harness::still_runs"), "/event", json!("ok"))?;
+  let summary = events.last().cloned().unwrap_or(Value::Null);
+  expect_field(&summary, "/passed", json!(2))?;
+  expect_field(&summary, "/failed", json!(1))
+
+}
+
+#[test]
+fn override_settings_from_the_command_line() -> LabResult {
+
+  let mut suite = describe_typed("cli", |ctx| {
+    ctx.it("math adds", |_spec| {
+      expect(1 + 1).to_equal(2)
+    });
+    ctx.it("math fails", |_spec| {
+      expect(1 + 1).to_equal(3)
+    });
+    ctx.it("math is not reached", |_spec| {
+      expect(2 + 2).to_equal(4)
+    });
+    ctx.it("strings are filtered out", |_spec| {
+      expect("a").to_equal("a")
+    });
+  }).spec().ignore_errors();
+
+  let path = std::env::temp_dir().join(format!("laboratory-cli-{}.json", std::process::id()));
+  let args = HarnessArgs::parse(vec![
+    "--reporter".to_string(), "json".to_string(),
+    "--grep=math".to_string(),
+    "--bail".to_string(),
+    "--output".to_string(), path.display().to_string()
+  ])?;
+  suite.run_harness(&args)?;
+  let report = std::fs::read_to_string(&path).map_err(|err| err.to_string());
+  let _ = std::fs::remove_file(&path);
+  let report: serde_json::Value = serde_json::from_str(&report?).map_err(|err| err.to_string())?;
+
+  let results: Vec<_> = suite.context.specs.iter().map(|spec| spec.result.clone()).collect();
+  expect(results).to_equal(vec![Some(Ok(())), Some(Err("Expected 2 to equal 3".to_string())), None])?;
+  expect((report["stats"]["failing"].as_u64(), report["stats"]["pending"].as_u64(), suite.filtered_out)).to_equal((Some(1), Some(1), 1))
+
+}
+
+#[test]
+fn read_defaults_from_a_config_file() -> LabResult {
+
+  let dir = std::env::temp_dir().join(format!("laboratory-config-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+  let path = dir.join(CONFIG_FILE);
+  std::fs::write(&path, "reporter = \"json\"\nduration_unit = \"ms\"\nslow = 20\ntimeout = 10\nretries = 2\noutput = \"lab.json\"\n")
+    .map_err(|err| err.to_string())?;
+  let config = Config::from_file(&path);
+  std::fs::write(&path, "reporter = \"xml\"\n").map_err(|err| err.to_string())?;
+  let invalid = Config::from_file(&path).is_err();
+  std::fs::write(&path, "retry = 2\n").map_err(|err| err.to_string())?;
+  let unknown = Config::from_file(&path).is_err();
+  let config = config?;
+
+  expect((config.slow, config.timeout, config.retries, config.output.clone(), invalid, unknown)).to_equal((
+    Some(Duration::from_millis(20)),
+    Some(Duration::from_millis(10)),
+    Some(2),
+    Some(dir.join("lab.json")),
+    true,
+    true
+  ))?;
+
+  let suite = || describe_typed("config", |ctx| {
+    ctx.it("is too slow", |_spec| {
+      sleep(Duration::from_millis(20));
+      Ok(())
+    });
+    ctx.describe("child", |ctx| {
+      ctx.it("is quick", |_spec| {
+        Ok(())
+      });
+    });
+  }).ignore_errors();
+
+  let mut with_defaults = suite();
+  config.apply_to_suite(&mut with_defaults);
+  with_defaults.run()?;
+  let report = std::fs::read_to_string(dir.join("lab.json")).map_err(|err| err.to_string());
+
+  // Builder calls made after the defaults are applied win over them.
+  let mut overridden = suite();
+  config.apply_to_suite(&mut overridden);
+  let mut overridden = overridden.timeout(Duration::from_secs(5)).output(dir.join("lab.txt")).min();
+  overridden.run()?;
+  let _ = std::fs::remove_dir_all(&dir);
+  let report: serde_json::Value = serde_json::from_str(&report?).map_err(|err| err.to_string())?;
+
+  let error = with_defaults.context.specs[0].result.clone().and_then(Result::err).unwrap_or_default();
+  expect((error.starts_with("Timeout of 10"), report["stats"]["failing"].as_u64(), with_defaults.context.suites[0].context.specs[0].result.clone())).to_equal((true, Some(1), Some(Ok(()))))?;
+  expect(overridden.context.specs[0].result.clone()).to_equal(Some(Ok(())))
+
+}
+
+#[test]
+fn layer_environment_variables_over_the_config_file() -> LabResult {
+
+  let dir = std::env::temp_dir().join(format!("laboratory-config-env-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+  let write = |name: &str, contents: &str| std::fs::write(dir.join(name), contents).map_err(|err| err.to_string());
+  let vars = |vars: Vec<(&'static str, &'static str)>| move |name: &str| {
+    vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+  };
+
+  // Without laboratory.toml the defaults come from the manifest.
+  write("Cargo.toml", "[package]\nname = \"demo\"\n\n[package.metadata.laboratory]\nreporter = \"dot\"\nretries = 1\n")?;
+  let from_manifest = Config::load_with(vars(vec![]), &dir);
+  let overridden = Config::load_with(vars(vec![("LAB_REPORTER", "tap"), ("LAB_SLOW", "50")]), &dir);
+  write(CONFIG_FILE, "duration_unit = \"us\"\nretries = 2\n")?;
+  let from_file = Config::load_with(vars(vec![("LAB_RETRIES", "3")]), &dir);
+  write("other.toml", "timeout = 5\n")?;
+  let from_config_var = Config::load_with(vars(vec![(CONFIG_VAR, "other.toml")]), &dir);
+  let invalid = Config::load_with(vars(vec![("LAB_TIMEOUT", "soon")]), &dir);
+  let _ = std::fs::remove_dir_all(&dir);
+
+  let from_manifest = from_manifest?;
+  let overridden = overridden?;
+  let from_file = from_file?;
+  let from_config_var = from_config_var?;
+  expect((from_manifest.reporter, from_manifest.retries)).to_equal((Some(Reporter::Dot), Some(1)))?;
+  expect((overridden.reporter, overridden.slow, overridden.retries)).to_equal((Some(Reporter::Tap), Some(Duration::from_millis(50)), Some(1)))?;
+  expect((from_file.reporter, from_file.duration_unit, from_file.retries)).to_equal((None, Some(DurationType::Micro), Some(3)))?;
+  expect((from_config_var.timeout, from_config_var.retries)).to_equal((Some(Duration::from_millis(5)), None))?;
+  expect(invalid).to_equal(Err("LAB_TIMEOUT must be a number (was soon)".to_string()))
+
+}